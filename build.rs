@@ -0,0 +1,51 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+//! Exposes the versions `Cargo.lock` resolved dependencies to, which `--diagnostics`
+//! reports, as `RESOLVED_<NAME>_VERSION`.
+
+use std::fs;
+
+/// The dependencies whose resolved version is exposed.
+const REPORTED: &[&str] = &["atspi"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let packages = fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| lock.parse::<toml::Table>().ok())
+        .and_then(|mut lock| match lock.remove("package")? {
+            toml::Value::Array(packages) => Some(packages),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let root = std::env::var("CARGO_PKG_NAME").unwrap();
+    for name in REPORTED {
+        let version = resolved_version(&packages, &root, name).unwrap_or("unknown");
+        let var = name.to_uppercase().replace('-', "_");
+        println!("cargo:rustc-env=RESOLVED_{var}_VERSION={version}");
+    }
+}
+
+/// The version of `name` the package `root` depends on, among the locked `packages`.
+///
+/// Lock files only spell out the version of a dependency when several versions of it
+/// are locked, e.g. `atspi 0.27.0`; otherwise the single locked package is the one.
+fn resolved_version<'a>(packages: &'a [toml::Value], root: &str, name: &str) -> Option<&'a str> {
+    let field = |package: &'a toml::Value, key| package.get(key)?.as_str();
+    let dependency = packages
+        .iter()
+        .find(|package| field(package, "name") == Some(root))?
+        .get("dependencies")?
+        .as_array()?
+        .iter()
+        .filter_map(toml::Value::as_str)
+        .find(|dependency| dependency.split(' ').next() == Some(name))?;
+    if let Some(version) = dependency.split(' ').nth(1) {
+        return Some(version);
+    }
+    packages
+        .iter()
+        .find(|package| field(package, "name") == Some(name))
+        .and_then(|package| field(package, "version"))
+}
@@ -0,0 +1,118 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::proxy::accessible::{AccessibleProxy, ObjectRefExt};
+use atspi::{AtspiError, ObjectRef, Role};
+use atspi_proxies::proxy_ext::ProxyExt;
+use serde::{Deserialize, Serialize};
+use zbus::Connection;
+
+use crate::error::Error;
+
+/// A running application registered with the accessibility bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Application {
+    pub object: ObjectRef,
+    pub name: String,
+}
+
+/// Returns a proxy for the desktop, the registry-provided root whose children are the
+/// running applications.
+pub async fn desktop_root(conn: &Connection) -> zbus::Result<AccessibleProxy<'static>> {
+    AccessibleProxy::builder(conn)
+        .destination("org.a11y.atspi.Registry")?
+        .path("/org/a11y/atspi/accessible/root")?
+        .build()
+        .await
+}
+
+/// Lists the applications currently registered with the accessibility bus.
+pub async fn list_applications(conn: &Connection) -> zbus::Result<Vec<Application>> {
+    let mut applications = Vec::new();
+
+    for object in desktop_root(conn).await?.get_children().await? {
+        let name = object.as_accessible_proxy(conn).await?.name().await?;
+        applications.push(Application { object, name });
+    }
+    Ok(applications)
+}
+
+/// Finds the application whose bus connection belongs to the process `pid`.
+pub async fn find_by_pid(conn: &Connection, pid: u32) -> Result<Application, Error> {
+    for application in list_applications(conn).await? {
+        // Applications that quit meanwhile cannot match either.
+        let owner = process_id(&application.object, conn).await;
+        if owner.is_ok_and(|owner| owner == pid) {
+            return Ok(application);
+        }
+    }
+    Err(Error::Invalid(format!(
+        "No application registered with the accessibility bus runs as PID {pid}"
+    )))
+}
+
+/// The toolkit an application was built with, as it reports it over AT-SPI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolkitInfo {
+    pub application: String,
+    pub toolkit: String,
+    pub version: String,
+    pub atspi_version: String,
+}
+
+/// Queries the version of the AT-SPI protocol the registry implements.
+pub async fn registry_version(conn: &Connection) -> Result<String, AtspiError> {
+    let registry = desktop_root(conn)
+        .await?
+        .proxies()
+        .await?
+        .application()
+        .await?;
+    Ok(registry.atspi_version().await?)
+}
+
+/// Queries the toolkit metadata of the application `object` belongs to.
+pub async fn toolkit_info(
+    object: &ObjectRef,
+    conn: &Connection,
+) -> Result<ToolkitInfo, AtspiError> {
+    let mut proxy = object.as_accessible_proxy(conn).await?;
+    if proxy.get_role().await? != Role::Application {
+        proxy = proxy
+            .get_application()
+            .await?
+            .into_accessible_proxy(conn)
+            .await?;
+    }
+    let application = proxy.proxies().await?.application().await?;
+
+    Ok(ToolkitInfo {
+        application: proxy.name().await?,
+        toolkit: application.toolkit_name().await?,
+        version: application.version().await?,
+        atspi_version: application.atspi_version().await?,
+    })
+}
+
+/// Whether `object` belongs to this process, i.e. is part of the overlay.
+pub async fn is_own(object: &ObjectRef, conn: &Connection) -> zbus::Result<bool> {
+    Ok(process_id(object, conn).await? == std::process::id())
+}
+
+/// The ID of the process owning the bus connection `object` is served from.
+pub async fn process_id(object: &ObjectRef, conn: &Connection) -> zbus::Result<u32> {
+    let dbus = zbus::fdo::DBusProxy::new(conn).await?;
+    Ok(dbus
+        .get_connection_unix_process_id(object.name.as_ref().into())
+        .await?)
+}
+
+/// Whether focus on `object` leaves nothing worth showing: it belongs to this process,
+/// i.e. the overlay itself, or it is the desktop.
+pub async fn is_idle_focus(object: &ObjectRef, conn: &Connection) -> zbus::Result<bool> {
+    if is_own(object, conn).await? {
+        return Ok(true);
+    }
+    let role = object.as_accessible_proxy(conn).await?.get_role().await?;
+    Ok(role == Role::DesktopFrame)
+}
@@ -0,0 +1,353 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::{CoordType, ObjectRef};
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+use zbus::Address;
+use zbus::names::UniqueName;
+use zbus::zvariant::ObjectPath;
+
+use crate::config::{BoxStyle, HexColor};
+use crate::node::Extents;
+use crate::predicate::StatePredicate;
+
+/// Parses an accessible address written as a bus name immediately followed by an object
+/// path, e.g. `:1.42/org/a11y/atspi/accessible/root`.
+fn parse_object_ref(address: &str) -> Result<ObjectRef, String> {
+    let split = address.find('/').ok_or_else(|| {
+        format!(
+            "'{address}' has no object path; expected e.g. ':1.42/org/a11y/atspi/accessible/root'"
+        )
+    })?;
+    let (name, path) = address.split_at(split);
+
+    let name = UniqueName::try_from(name)
+        .map_err(|err| format!("'{name}' is not a valid unique bus name: {err}"))?;
+    let path = ObjectPath::try_from(path)
+        .map_err(|err| format!("'{path}' is not a valid object path: {err}"))?;
+
+    Ok(ObjectRef::new(name, path))
+}
+
+/// Parses a positive number of seconds.
+fn parse_interval(seconds: &str) -> Result<f64, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err(format!("'{seconds}' is not a positive number of seconds")),
+    }
+}
+
+/// A JSON dump drawn over the overlay, shifted by `offset` pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlaySource {
+    pub path: PathBuf,
+    pub offset: (i32, i32),
+}
+
+/// Parses a path to a JSON dump, optionally followed by `@DX,DY`, e.g. `before.json@0,40`.
+fn parse_overlay(source: &str) -> Result<OverlaySource, String> {
+    let Some((path, offset)) = source.rsplit_once('@') else {
+        return Ok(OverlaySource {
+            path: source.into(),
+            offset: (0, 0),
+        });
+    };
+    let parse = |part: &str| part.trim().parse::<i32>().ok();
+    let offset = offset
+        .split_once(',')
+        .and_then(|(dx, dy)| Some((parse(dx)?, parse(dy)?)))
+        .ok_or_else(|| format!("'{offset}' is not an offset like DX,DY"))?;
+    Ok(OverlaySource {
+        path: path.into(),
+        offset,
+    })
+}
+
+/// Parses a non-negative number of seconds.
+fn parse_delay(seconds: &str) -> Result<f64, String> {
+    match seconds.parse::<f64>() {
+        Ok(seconds) if seconds >= 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err(format!(
+            "'{seconds}' is not a non-negative number of seconds"
+        )),
+    }
+}
+
+/// Parses a percentage between 0 and 100.
+fn parse_percent(percent: &str) -> Result<f64, String> {
+    match percent.parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("'{percent}' is not a percentage between 0 and 100")),
+    }
+}
+
+/// Which origin extents and hit test points are given relative to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Coordinates {
+    /// The top-left corner of the screen.
+    #[default]
+    Screen,
+    /// The top-left corner of each node's top-level window.
+    Window,
+}
+
+impl From<Coordinates> for CoordType {
+    fn from(coordinates: Coordinates) -> Self {
+        match coordinates {
+            Coordinates::Screen => CoordType::Screen,
+            Coordinates::Window => CoordType::Window,
+        }
+    }
+}
+
+/// Whether this is a Wayland session, where AT-SPI screen coordinates are often missing
+/// or all relative to each window.
+pub fn is_wayland() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// The kind of graphical session, e.g. `x11` or `wayland`, as announced by
+/// `XDG_SESSION_TYPE` or else guessed from the display variables.
+pub fn session_type() -> String {
+    match std::env::var("XDG_SESSION_TYPE") {
+        Ok(session) if !session.is_empty() => session,
+        _ if std::env::var_os("WAYLAND_DISPLAY").is_some() => "wayland".into(),
+        _ if std::env::var_os("DISPLAY").is_some() => "x11".into(),
+        _ => "unknown".into(),
+    }
+}
+
+/// Overlay a box on every accessible element exposed over AT-SPI.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about)]
+pub struct Args {
+    /// Config file to load and persist settings in [default:
+    /// $XDG_CONFIG_HOME/atspi-tree-visualizer/config.toml]
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the visualizer's and the atspi library's versions, the session type, and the
+    /// AT-SPI version the registry reports, for bug reports, then exit
+    #[arg(long, conflicts_with = "replay")]
+    pub diagnostics: bool,
+
+    /// Check that the accessibility bus and its registry respond, print the outcome of
+    /// each step, and exit without opening the overlay; fails with a non-zero status
+    #[arg(long, conflicts_with = "replay")]
+    pub check: bool,
+
+    /// Print the applications registered with the accessibility bus with their process
+    /// IDs, toolkits, and addresses, e.g. for `--pid` or `--root`, and exit
+    #[arg(long, conflicts_with_all = ["replay", "check"])]
+    pub list_apps: bool,
+
+    /// Print `--list-apps` as tab-separated name, PID, toolkit, and address without a
+    /// header, for scripts; unknown fields are left empty
+    #[arg(long, requires = "list_apps")]
+    pub quiet: bool,
+
+    /// Connect to the accessibility bus at this D-Bus address (e.g.
+    /// `unix:path=/tmp/at-spi-bus`) instead of the one announced on the session bus
+    #[arg(long, value_name = "ADDRESS")]
+    pub bus_address: Option<Address>,
+
+    /// Pause collecting and drawing while the overlay itself or the desktop has focus,
+    /// resuming once an application gains focus
+    #[arg(long)]
+    pub idle_when_unfocused: bool,
+
+    /// Start with the overlay hidden until it is revealed with `O`; trees are still
+    /// collected in the meantime
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// Re-assert that the overlay stays above other windows every this many seconds, for
+    /// window managers that lower it; otherwise it is only requested at startup
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval)]
+    pub on_top_interval: Option<f64>,
+
+    /// Fill the overlay background with this color (`#rrggbb` or `#rrggbbaa`), e.g.
+    /// `#00ff00` for chroma keying, overriding the config file [default: transparent]
+    #[arg(long, value_name = "COLOR")]
+    pub background: Option<HexColor>,
+
+    /// How node boxes are drawn, overriding `box_style` in the config file for this run
+    /// only [default: outline]
+    #[arg(long, value_name = "STYLE")]
+    pub style: Option<BoxStyle>,
+
+    /// Collect from this accessible instead of each newly loaded document, given as a bus
+    /// name followed by an object path (e.g. `:1.42/org/a11y/atspi/accessible/123`)
+    #[arg(long, value_name = "ADDRESS", value_parser = parse_object_ref)]
+    pub root: Option<ObjectRef>,
+
+    /// Collect from the application run by this process, which tells instances of the same
+    /// program apart where their names do not
+    #[arg(long, value_name = "PID", conflicts_with_all = ["root", "replay"])]
+    pub pid: Option<u32>,
+
+    /// Only outline the top-level window of the focused application instead of
+    /// collecting whole trees
+    #[arg(long, conflicts_with_all = ["root", "pid"])]
+    pub focused_window: bool,
+
+    /// Record the order in which nodes receive focus and draw it as a numbered trail
+    #[arg(long)]
+    pub focus_trail: bool,
+
+    /// Only add nodes to the focus trail that keep focus for this many seconds, so focus
+    /// bouncing through transient elements leaves no trace; 0 records every change
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.15, value_parser = parse_delay)]
+    pub focus_dwell: f64,
+
+    /// Probe the bus after this many seconds without events and, if it does not answer,
+    /// subscribe to events again; 0 disables the watchdog
+    #[arg(long, value_name = "SECONDS", default_value_t = 30.0, value_parser = parse_delay)]
+    pub event_watchdog: f64,
+
+    /// Wait this many seconds after a document loads before collecting it, giving the
+    /// application time to finish populating its tree
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.1, value_parser = parse_delay)]
+    pub settle_delay: f64,
+
+    /// Keep a status line at the bottom of the terminal showing the node count, frame
+    /// rate, application, and errors of the last minute; log output is printed above it
+    #[arg(long)]
+    pub tui_status: bool,
+
+    /// Stay on the first document that finishes loading, ignoring documents loaded after
+    /// it, e.g. iframes, until `U` releases it
+    #[arg(long, conflicts_with_all = ["root", "pid", "focused_window", "applications"])]
+    pub first_only: bool,
+
+    /// Show one running application at a time, cycling through them with `N` and `P`
+    #[arg(long, conflicts_with_all = ["root", "pid", "focused_window"])]
+    pub applications: bool,
+
+    /// Leave the application with this name out of `--applications`, in addition to
+    /// those in the config's `exclude_applications`; can be repeated
+    #[arg(long, value_name = "NAME", requires = "applications")]
+    pub exclude_app: Vec<String>,
+
+    /// Only collect nodes whose states match this expression, e.g. `showing & focusable &
+    /// !editable`
+    #[arg(long, value_name = "EXPRESSION", default_value = "showing")]
+    pub states: StatePredicate,
+
+    /// Cover only this monitor, counted from 0 in the order `xrandr --listactivemonitors`
+    /// lists them, and only collect nodes on it
+    #[arg(long, value_name = "N")]
+    pub monitor: Option<usize>,
+
+    /// Only collect nodes intersecting this screen region, given as `x,y,width,height`;
+    /// in interactive mode it can be redrawn with Shift+drag
+    #[arg(long, value_name = "X,Y,W,H")]
+    pub region: Option<Extents>,
+
+    /// Position boxes relative to the screen or to each node's window; window-relative
+    /// boxes line up only for windows at the top-left corner of the screen [default:
+    /// window on Wayland, screen elsewhere]
+    #[arg(long, value_enum, value_name = "ORIGIN")]
+    pub coordinates: Option<Coordinates>,
+
+    /// Retry a failed extents query this many times before drawing the node without a box
+    #[arg(long, value_name = "COUNT", default_value_t = 2)]
+    pub extents_retries: u32,
+
+    /// Write each collected tree as JSON to this path (`-` for stdout)
+    #[arg(long, value_name = "PATH")]
+    pub export_json: Option<PathBuf>,
+
+    /// Write each collected tree as an SVG drawing of its boxes and labels to this path;
+    /// `S` also saves the tree on screen there [default for `S`: atspi-tree.svg]
+    #[arg(long, value_name = "PATH")]
+    pub export_svg: Option<PathBuf>,
+
+    /// Write each collected tree's hierarchy as a GraphViz DOT graph to this path
+    #[arg(long, value_name = "PATH")]
+    pub export_dot: Option<PathBuf>,
+
+    /// Time the D-Bus queries made for each node and write them for each collected tree to
+    /// this path as folded stacks of the node hierarchy, for flamegraph tools such as
+    /// `inferno-flamegraph`; counts are in microseconds
+    #[arg(long, value_name = "PATH")]
+    pub export_folded: Option<PathBuf>,
+
+    /// Make `S` save only the nodes drawn at full opacity, leaving out those hidden by the
+    /// max depth, lint mode, or a solo; per-collection exports always hold the whole tree
+    #[arg(long)]
+    pub export_filtered: bool,
+
+    /// Append each collected tree with a timestamp to this NDJSON session file, starting
+    /// right away; `W` stops and restarts recording [default for `W`: atspi-session.ndjson]
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Stream each collected tree to viewers connecting to this address, e.g.
+    /// 0.0.0.0:7878, as NDJSON session frames
+    #[arg(long, value_name = "HOST:PORT", conflicts_with = "replay")]
+    pub serve: Option<String>,
+
+    /// With `--serve`, collect without showing an overlay, e.g. on a device without a
+    /// display; runs until interrupted or for `--duration`
+    #[arg(long, requires = "serve")]
+    pub headless: bool,
+
+    /// Exit after running for this many seconds, e.g. to record a session or capture
+    /// trees from a script
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval)]
+    pub duration: Option<f64>,
+
+    /// Show the trees streamed by a `--serve` collector at this address in a window
+    /// instead of connecting to the accessibility bus, reconnecting when the connection
+    /// drops
+    #[arg(
+        long,
+        value_name = "HOST:PORT",
+        conflicts_with_all = ["replay", "serve", "root", "pid", "applications", "focused_window", "record", "diff_baseline"]
+    )]
+    pub connect: Option<String>,
+
+    /// Show the trees of a recorded session on a timeline instead of connecting to the
+    /// accessibility bus; the overlay takes the pointer meanwhile
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["root", "focused_window", "applications", "record"])]
+    pub replay: Option<PathBuf>,
+
+    /// Also draw the tree of this `--export-json` dump, shifted by DX,DY pixels if given,
+    /// in its own color listed in a legend; can be repeated to compare several dumps
+    #[arg(long, value_name = "PATH[@DX,DY]", value_parser = parse_overlay)]
+    pub overlay: Vec<OverlaySource>,
+
+    /// Collect the tree below `--root` or `--pid` once, print how it differs from this JSON
+    /// dump, and exit with status 1 if there are more differences than
+    /// `--diff-threshold`; nodes are matched by their role and name path, ignoring extents
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    pub diff_baseline: Option<PathBuf>,
+
+    /// How many added, removed, or changed nodes `--diff-baseline` tolerates
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 0,
+        requires = "diff_baseline"
+    )]
+    pub diff_threshold: usize,
+
+    /// Replace accessible names, descriptions, typed text, and link targets in exports with
+    /// deterministic placeholders
+    #[arg(long)]
+    pub anonymize: bool,
+
+    /// Log an error for every collected tree in which fewer than this percentage of
+    /// interactive nodes have a name, and show the coverage in red in lint mode; with
+    /// `--headless` or `--diff-baseline`, also exit with status 1
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+    pub min_name_coverage: Option<f64>,
+
+    /// Query sliders, spinners, and progress bars for their value and draw it on the overlay,
+    /// and editable text nodes for their contents, so either can be searched for
+    #[arg(long)]
+    pub show_values: bool,
+}
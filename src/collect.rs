@@ -0,0 +1,990 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::proxy::accessible::{AccessibleProxy, ObjectRefExt};
+use atspi::{AtspiError, CoordType, ObjectRef, Role, State, StateSet};
+use atspi_proxies::hyperlink::HyperlinkProxy;
+use atspi_proxies::proxy_ext::{Proxies, ProxyExt};
+use log::{debug, error, info};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use zbus::Connection;
+
+use crate::cli::{Args, Coordinates};
+use crate::config::LintConfig;
+use crate::error::Error;
+use crate::node::{
+    Extents, Hyperlink, NodeInfo, NodeValue, SelectionInfo, TableCell, TableInfo, TextContrast,
+    address, label,
+};
+use crate::predicate::StatePredicate;
+use crate::stats::{CollectionStats, TraversalProfile};
+
+/// Whether `object` is the null reference AT-SPI uses for a missing object, e.g. the
+/// parent of the desktop root.
+///
+/// The spec uses `/org/a11y/atspi/null`, while the `atspi` crate's default is
+/// `/org/a11y/atspi/accessible/null`; both are treated as null.
+pub fn is_null(object: &ObjectRef) -> bool {
+    matches!(
+        object.path.as_str(),
+        "/org/a11y/atspi/null" | "/org/a11y/atspi/accessible/null"
+    )
+}
+
+/// Pause between two attempts at querying a node's extents.
+const EXTENTS_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// Most selected children resolved per container, as each one costs several queries.
+pub const MAX_SELECTED_CHILDREN: i32 = 200;
+
+/// How often listing the children of the collection root is retried before the collection
+/// fails, unless the root turns out to be gone.
+const ROOT_RETRIES: u32 = 2;
+
+/// Pause between two attempts at listing the children of the collection root.
+const ROOT_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Controls which optional data is resolved for each collected node.
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Which nodes are collected; descendants of rejected nodes are still traversed.
+    pub predicate: StatePredicate,
+    /// Query the Value interface of nodes that implement it.
+    pub values: bool,
+    /// Screen region collected nodes must intersect, if any.
+    pub region: Option<Extents>,
+    /// Origin extents are queried relative to.
+    pub coordinates: Coordinates,
+    /// How often a failed extents query is retried before the node is left without extents.
+    pub extents_retries: u32,
+    /// Most cells resolved per table, if tables are queried at all.
+    pub table_cells: Option<usize>,
+    /// Query the links embedded in the text of hypertext nodes.
+    pub hyperlinks: bool,
+    /// Query the selected children of containers implementing Selection.
+    pub selections: bool,
+    /// Most children traversed per node, if capped.
+    pub max_children: Option<usize>,
+    /// Query the text colors of nodes implementing Text to check their contrast.
+    pub contrast: bool,
+    /// Time the queries made for each node, see `TraversalProfile`.
+    pub profile: bool,
+    /// Thresholds of the lint heuristics counted in the stats, e.g. the minimum target
+    /// size.
+    pub lint: LintConfig,
+    /// Roles whose children are not traversed when a node with that role is collected.
+    pub stop_roles: Arc<[Role]>,
+}
+
+impl From<&Args> for CollectOptions {
+    fn from(args: &Args) -> Self {
+        Self {
+            predicate: args.states,
+            values: args.show_values,
+            region: args.region,
+            coordinates: args.coordinates.unwrap_or_default(),
+            extents_retries: args.extents_retries,
+            table_cells: None,
+            hyperlinks: false,
+            selections: false,
+            max_children: None,
+            contrast: false,
+            profile: args.export_folded.is_some(),
+            lint: LintConfig::default(),
+            stop_roles: Arc::new([]),
+        }
+    }
+}
+
+/// Starts a depth-first search of the tree below `root` in the background, yielding each
+/// node as soon as it is resolved. The returned task finishes with the traversal's
+/// statistics, or the error that aborted it.
+///
+/// Only nodes matching the state predicate (by default, those that are showing) are
+/// collected, but the descendants of rejected nodes are still traversed; a collected
+/// node's `parent` is its nearest collected ancestor, which is always yielded first.
+/// Defunct nodes are skipped along with their subtrees, as are nodes that fail to answer
+/// a query; only a failure to reach the root aborts the collection.
+///
+/// With a region set, collected nodes whose extents lie outside it are culled along with
+/// their subtrees, assuming children are laid out within their parent; nodes without
+/// extents are not culled. Collected nodes with one of the stop roles are kept but their
+/// children are not traversed, and with `max_children` set only that many children of any
+/// node are.
+///
+/// Dropping the stream cancels the traversal once the query in flight is answered; the
+/// task then finishes with the statistics gathered so far, marked as cancelled.
+pub fn stream_children(
+    root: ObjectRef,
+    conn: Arc<Connection>,
+    options: CollectOptions,
+) -> (
+    UnboundedReceiverStream<NodeInfo>,
+    JoinHandle<Result<CollectionStats, Error>>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let traversal = tokio::spawn(async move {
+        // Building the proxy sends nothing, so a stale root only shows once queried.
+        let root = root.into_accessible_proxy(&conn).await?;
+        traverse(&Bus(&conn), root, &options, &tx).await
+    });
+    (UnboundedReceiverStream::new(rx), traversal)
+}
+
+/// The tree `traverse` walks: the accessibility bus, or a fake tree in tests.
+trait Tree {
+    /// A node whose children can be listed.
+    type Node;
+
+    /// The children of `node`; those of the collection `root` are retried while they may
+    /// be unavailable only for a moment.
+    async fn children(&self, node: &Self::Node, root: bool) -> Result<Vec<ObjectRef>, Error>;
+
+    /// The node `child` refers to, along with its state.
+    async fn node(&self, child: &ObjectRef) -> Result<(Self::Node, StateSet), Error>;
+
+    /// Resolves the node `object` refers to, see `resolve_node`.
+    async fn resolve(
+        &self,
+        node: &Self::Node,
+        object: ObjectRef,
+        states: StateSet,
+        parent: Option<usize>,
+        depth: usize,
+        options: &CollectOptions,
+    ) -> Result<NodeInfo, Error>;
+}
+
+/// The tree of accessibles on the bus `0` is connected to.
+struct Bus<'c>(&'c Connection);
+
+impl<'c> Tree for Bus<'c> {
+    type Node = AccessibleProxy<'c>;
+
+    async fn children(&self, node: &Self::Node, root: bool) -> Result<Vec<ObjectRef>, Error> {
+        if root {
+            root_children(node).await
+        } else {
+            node.get_children().await.map_err(Error::from)
+        }
+    }
+
+    async fn node(&self, child: &ObjectRef) -> Result<(Self::Node, StateSet), Error> {
+        let proxy = child.clone().into_accessible_proxy(self.0).await?;
+        let state = proxy.get_state().await?;
+        Ok((proxy, state))
+    }
+
+    async fn resolve(
+        &self,
+        node: &Self::Node,
+        object: ObjectRef,
+        states: StateSet,
+        parent: Option<usize>,
+        depth: usize,
+        options: &CollectOptions,
+    ) -> Result<NodeInfo, Error> {
+        resolve_node(node, object, states, parent, depth, options).await
+    }
+}
+
+async fn traverse<T: Tree>(
+    tree: &T,
+    root: T::Node,
+    options: &CollectOptions,
+    tx: &UnboundedSender<NodeInfo>,
+) -> Result<CollectionStats, Error> {
+    let started = Instant::now();
+    let mut stats = CollectionStats::default();
+    let mut profile = options.profile.then(TraversalProfile::default);
+    let listed = Instant::now();
+    let children = tree.children(&root, true).await?;
+    if let Some(profile) = &mut profile {
+        profile.charge(None, listed.elapsed());
+    }
+    let limit = options.max_children.unwrap_or(usize::MAX);
+    let mut stack = vec![(children, None, 0)];
+    'traversal: while let Some((children, parent, depth)) = stack.pop() {
+        for child in children.into_iter().take(limit) {
+            // Checked before every query, as rejected nodes are traversed without ever
+            // sending anything that would notice the dropped stream.
+            if tx.is_closed() {
+                debug!("Node stream dropped, stopping traversal");
+                stats.cancelled = true;
+                break 'traversal;
+            }
+            let queried = Instant::now();
+            let child_state = tree.node(&child).await;
+            if let Some(profile) = &mut profile {
+                profile.charge(parent, queried.elapsed());
+            }
+            let (child_node, state) = match child_state {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Error: Failed to get state for {}: {err}", address(&child));
+                    stats.errored += 1;
+                    continue;
+                }
+            };
+
+            if skip_child(&state) {
+                debug!("Skipping defunct node {}", address(&child));
+                stats.skipped += 1;
+                continue;
+            }
+
+            let mut child_parent = parent;
+            let mut collected = None;
+            if options.predicate.matches(state) {
+                let address = address(&child);
+                let resolved = Instant::now();
+                let node = tree
+                    .resolve(&child_node, child, state, parent, depth + 1, options)
+                    .await;
+                // Charged to the node itself if it is collected, else to its parent.
+                let cost = resolved.elapsed();
+                let node = match node {
+                    Ok(node) => node,
+                    Err(err) => {
+                        error!("Error: Failed to resolve {address}: {err}");
+                        stats.errored += 1;
+                        if let Some(profile) = &mut profile {
+                            profile.charge(parent, cost);
+                        }
+                        continue;
+                    }
+                };
+                if let (Some(region), Some(extents)) = (options.region, node.extents)
+                    && !region.intersects(&extents)
+                {
+                    if let Some(profile) = &mut profile {
+                        profile.charge(parent, cost);
+                    }
+                    continue;
+                }
+                child_parent = Some(stats.total);
+                if let Some(profile) = &mut profile {
+                    profile.charge(child_parent, cost);
+                }
+                collected = Some(node);
+            }
+
+            // Listed before the node is sent, so it can tell how many children are left
+            // out of the traversal.
+            let stop = collected
+                .as_ref()
+                .is_some_and(|node| options.stop_roles.contains(&node.role));
+            let mut grandchildren = Vec::new();
+            if !stop {
+                let listed = Instant::now();
+                match tree.children(&child_node, false).await {
+                    Ok(children) => grandchildren = children,
+                    Err(err) => {
+                        error!("Error: Failed to get children: {err}");
+                        stats.errored += 1;
+                    }
+                }
+                if let Some(profile) = &mut profile {
+                    profile.charge(child_parent, listed.elapsed());
+                }
+            }
+
+            if let Some(mut node) = collected {
+                if options.max_children.is_some() {
+                    node.truncated_children = truncation(grandchildren.len(), limit);
+                }
+                stats.record(&node, &options.lint);
+                if tx.send(node).is_err() {
+                    debug!("Node stream dropped, stopping traversal");
+                    stats.cancelled = true;
+                    break 'traversal;
+                }
+            }
+            if !grandchildren.is_empty() {
+                stack.push((grandchildren, child_parent, depth + 1));
+            }
+        }
+    }
+
+    stats.elapsed_secs = started.elapsed().as_secs_f64();
+    stats.profile = profile;
+    Ok(stats)
+}
+
+/// Lists the children of the collection root, retrying failures that may be transient,
+/// e.g. while the application is still busy right after a document loaded. A root that is
+/// gone fails at once.
+async fn root_children(root: &AccessibleProxy<'_>) -> Result<Vec<ObjectRef>, Error> {
+    let mut retries = 0;
+    loop {
+        match root.get_children().await.map_err(Error::from) {
+            Err(err) if retries < ROOT_RETRIES && !err.is_stale() => {
+                retries += 1;
+                debug!("Retrying to list the children of the collection root: {err}");
+                tokio::time::sleep(ROOT_RETRY_DELAY).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether a child in the `state` is left out of the traversal entirely. Defunct objects
+/// have been destroyed but can linger in a stale child list; any further query on them
+/// would only fail.
+fn skip_child(state: &StateSet) -> bool {
+    state.contains(State::Defunct)
+}
+
+/// Resolves the metadata and geometry of a single node.
+pub async fn resolve_node(
+    proxy: &AccessibleProxy<'_>,
+    object: ObjectRef,
+    states: StateSet,
+    parent: Option<usize>,
+    depth: usize,
+    options: &CollectOptions,
+) -> Result<NodeInfo, Error> {
+    let (mut extents, mut value, mut text) = (None, None, None);
+    let (mut table, mut hyperlinks, mut selection, mut contrast) = (None, None, None, None);
+    match proxy.proxies().await {
+        Ok(proxies) => {
+            extents = resolve_extents(&proxies, &object, options).await;
+            if options.values {
+                value = resolve_value(&proxies, &object).await;
+                if states.contains(State::Editable) {
+                    text = resolve_text(&proxies, &object).await;
+                }
+            }
+            if let Some(max_cells) = options.table_cells {
+                let conn = proxy.inner().connection();
+                table = resolve_table(&proxies, &object, conn, options, max_cells).await;
+            }
+            if options.hyperlinks {
+                let conn = proxy.inner().connection();
+                hyperlinks = resolve_hyperlinks(&proxies, &object, conn, options).await;
+            }
+            if options.selections {
+                let conn = proxy.inner().connection();
+                selection = resolve_selection(&proxies, &object, conn, options).await;
+            }
+            if options.contrast {
+                contrast = resolve_contrast(&proxies, &object).await;
+            }
+        }
+        Err(err) => error!(
+            "Error: Failed to get proxies from proxy for {}: {err}",
+            address(&object)
+        ),
+    }
+
+    Ok(NodeInfo {
+        role: proxy.get_role().await?,
+        name: proxy.name().await?,
+        description: proxy.description().await?,
+        object,
+        parent,
+        depth,
+        states,
+        extents,
+        value,
+        text,
+        table,
+        selection,
+        hyperlinks,
+        contrast,
+        truncated_children: None,
+        both_extents: None,
+        interfaces: None,
+        actions: None,
+    })
+}
+
+/// The child `count` of a node if `max_children` leaves some of them out.
+fn truncation(count: usize, max_children: usize) -> Option<usize> {
+    (count > max_children).then_some(count)
+}
+
+/// Resolves a single node outside of a collection, e.g. one that just received focus.
+/// It is given no parent and depth 0.
+pub async fn resolve_detached(
+    proxy: &AccessibleProxy<'_>,
+    object: ObjectRef,
+    options: &CollectOptions,
+) -> Result<NodeInfo, Error> {
+    let states = proxy.get_state().await?;
+    resolve_node(proxy, object, states, None, 0, options).await
+}
+
+/// Walks up from `object` to the top-level window containing it, i.e. the ancestor
+/// whose parent is the application.
+pub async fn toplevel_window<'c>(
+    object: ObjectRef,
+    conn: &'c Connection,
+) -> Result<(ObjectRef, AccessibleProxy<'c>), Error> {
+    let mut current = object;
+    let mut proxy = current.clone().into_accessible_proxy(conn).await?;
+
+    loop {
+        let parent = proxy.parent().await?;
+        if is_null(&parent) {
+            return Ok((current, proxy));
+        }
+
+        let parent_proxy = parent.clone().into_accessible_proxy(conn).await?;
+        if parent_proxy.get_role().await? == Role::Application {
+            return Ok((current, proxy));
+        }
+
+        current = parent;
+        proxy = parent_proxy;
+    }
+}
+
+/// An ancestor of a collection root, as shown in the breadcrumb.
+#[derive(Debug, Clone)]
+pub struct Crumb {
+    pub object: ObjectRef,
+    /// The ancestor's role and name, as in `NodeInfo::label`.
+    pub label: String,
+}
+
+/// Walks up from `object` to its application, returning the path from the application
+/// down to `object` itself.
+pub async fn ancestry(object: ObjectRef, conn: &Connection) -> Result<Vec<Crumb>, Error> {
+    let mut crumbs = Vec::new();
+    let mut current = object;
+
+    loop {
+        let proxy = current.clone().into_accessible_proxy(conn).await?;
+        let role = proxy.get_role().await?;
+        if role == Role::DesktopFrame {
+            break;
+        }
+        let name = proxy.name().await?;
+        let parent = proxy.parent().await?;
+        crumbs.push(Crumb {
+            object: current,
+            label: label(role, &name),
+        });
+        if role == Role::Application || is_null(&parent) {
+            break;
+        }
+        current = parent;
+    }
+
+    crumbs.reverse();
+    Ok(crumbs)
+}
+
+/// Asks AT-SPI for the deepest accessible below `root` at the point `(x, y)`, given
+/// relative to `coordinates`.
+///
+/// Descends through `get_accessible_at_point` for as long as the current node implements
+/// Component; nodes that do not, such as applications, are entered through the first
+/// child whose Component contains the point. Returns `None` if no node answered.
+pub async fn accessible_at_point(
+    root: ObjectRef,
+    x: i32,
+    y: i32,
+    coordinates: Coordinates,
+    conn: &Connection,
+) -> Result<Option<ObjectRef>, Error> {
+    let coordinates = CoordType::from(coordinates);
+    let mut current = root.into_accessible_proxy(conn).await?;
+    let mut found = None;
+
+    loop {
+        let next = match current.proxies().await?.component().await {
+            Ok(component) => {
+                let child = component.get_accessible_at_point(x, y, coordinates).await?;
+                (!is_null(&child) && found.as_ref() != Some(&child)).then_some(child)
+            }
+            Err(_) => {
+                let mut containing = None;
+                for child in current.get_children().await? {
+                    let proxy = child.clone().into_accessible_proxy(conn).await?;
+                    if let Ok(component) = proxy.proxies().await?.component().await
+                        && component.contains(x, y, coordinates).await?
+                    {
+                        containing = Some(child);
+                        break;
+                    }
+                }
+                containing
+            }
+        };
+
+        let Some(next) = next else {
+            return Ok(found);
+        };
+        current = next.clone().into_accessible_proxy(conn).await?;
+        found = Some(next);
+    }
+}
+
+/// Runs `attempt` until it succeeds or `retries` retries are used up, sleeping `delay`
+/// between attempts. Returns the last result along with the number of retries used.
+pub async fn retry<T, E, Fut>(
+    retries: u32,
+    delay: Duration,
+    mut attempt: impl FnMut() -> Fut,
+) -> (Result<T, E>, u32)
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut used = 0;
+    loop {
+        match attempt().await {
+            Err(_) if used < retries => {
+                used += 1;
+                tokio::time::sleep(delay).await;
+            }
+            result => return (result, used),
+        }
+    }
+}
+
+/// Fetches the extents of a node, returning `None` if it has none.
+///
+/// Nodes without the Component interface, such as applications, cannot be placed on
+/// screen at all, which is only noted in the debug log; failed queries are logged as
+/// errors. Busy buses occasionally fail the query transiently, so it is attempted up to
+/// `extents_retries` more times, `EXTENTS_RETRY_DELAY` apart.
+async fn resolve_extents(
+    proxies: &Proxies<'_>,
+    object: &ObjectRef,
+    options: &CollectOptions,
+) -> Option<Extents> {
+    let coordinates = CoordType::from(options.coordinates);
+    match proxies.component().await {
+        Ok(component) => match retry(options.extents_retries, EXTENTS_RETRY_DELAY, || {
+            component.get_extents(coordinates)
+        })
+        .await
+        {
+            (Ok(extents), 0) => Some(Extents::new(extents)),
+            (Ok(extents), used) => {
+                info!("Got extents for {} after {used} retries", address(object));
+                Some(Extents::new(extents))
+            }
+            (Err(err), _) => {
+                error!(
+                    "Error: Failed to get extents from component for {}: {err}",
+                    address(object)
+                );
+                None
+            }
+        },
+        Err(AtspiError::InterfaceNotAvailable(_)) => {
+            debug!(
+                "{} does not implement Component, so it has no extents",
+                address(object)
+            );
+            None
+        }
+        Err(err) => {
+            error!(
+                "Error: Failed to get component from proxies for {}: {err}",
+                address(object)
+            );
+            None
+        }
+    }
+}
+
+/// Fetches the extents of `object`, which is not part of the collection, e.g. a table
+/// cell; `None` if it is null or has none.
+async fn object_extents(
+    object: ObjectRef,
+    conn: &Connection,
+    options: &CollectOptions,
+) -> Option<Extents> {
+    if is_null(&object) {
+        return None;
+    }
+    let proxy = object.clone().into_accessible_proxy(conn).await.ok()?;
+    let proxies = proxy.proxies().await.ok()?;
+    resolve_extents(&proxies, &object, options).await
+}
+
+/// Fetches the dimensions, headers, and up to `max_cells` cells of a table, logging and
+/// returning `None` if the node is not a table or the query fails.
+async fn resolve_table(
+    proxies: &Proxies<'_>,
+    object: &ObjectRef,
+    conn: &Connection,
+    options: &CollectOptions,
+    max_cells: usize,
+) -> Option<TableInfo> {
+    let table = proxies.table().await.ok()?;
+    let (rows, columns) = match futures::try_join!(table.nrows(), table.ncolumns()) {
+        Ok(dimensions) => dimensions,
+        Err(err) => {
+            error!(
+                "Error: Failed to get dimensions of table {}: {err}",
+                address(object)
+            );
+            return None;
+        }
+    };
+
+    let mut cells = Vec::new();
+    let mut seen = HashSet::new();
+    let headers = (0..columns)
+        .map(|column| (true, column))
+        .chain((0..rows).map(|row| (false, row)));
+    for (is_column, index) in headers {
+        let header = if is_column {
+            table.get_column_header(index).await
+        } else {
+            table.get_row_header(index).await
+        };
+        if let Ok(header) = header
+            && seen.insert(header.clone())
+            && let Some(extents) = object_extents(header, conn, options).await
+        {
+            cells.push(TableCell {
+                extents,
+                header: true,
+            });
+        }
+    }
+
+    let positions = (0..rows).flat_map(|row| (0..columns).map(move |column| (row, column)));
+    for (row, column) in positions {
+        if cells.len() >= max_cells {
+            debug!("Table {} has more than {max_cells} cells", address(object));
+            break;
+        }
+        let Ok(cell) = table.get_accessible_at(row, column).await else {
+            continue;
+        };
+        if seen.insert(cell.clone())
+            && let Some(extents) = object_extents(cell, conn, options).await
+        {
+            cells.push(TableCell {
+                extents,
+                header: false,
+            });
+        }
+    }
+
+    Some(TableInfo {
+        rows,
+        columns,
+        cells,
+    })
+}
+
+/// Fetches the number and extents of the selected children of a container, up to
+/// `MAX_SELECTED_CHILDREN`. Nodes without the Selection interface yield `None` silently;
+/// query failures are logged.
+async fn resolve_selection(
+    proxies: &Proxies<'_>,
+    object: &ObjectRef,
+    conn: &Connection,
+    options: &CollectOptions,
+) -> Option<SelectionInfo> {
+    let selection = proxies.selection().await.ok()?;
+    let count = match selection.nselected_children().await {
+        Ok(count) => count,
+        Err(err) => {
+            error!(
+                "Error: Failed to count selected children of {}: {err}",
+                address(object)
+            );
+            return None;
+        }
+    };
+
+    let mut selected = Vec::new();
+    for index in 0..count.min(MAX_SELECTED_CHILDREN) {
+        match selection.get_selected_child(index).await {
+            Ok(child) => selected.extend(object_extents(child, conn, options).await),
+            Err(err) => debug!(
+                "Failed to get selected child {index} of {}: {err}",
+                address(object)
+            ),
+        }
+    }
+    Some(SelectionInfo { count, selected })
+}
+
+/// Fetches the links embedded in the text of a node, with the bounds of each link's text
+/// if the node also implements Text. Nodes without the Hypertext interface yield `None`
+/// silently; links failing to answer are logged and left out.
+async fn resolve_hyperlinks(
+    proxies: &Proxies<'_>,
+    object: &ObjectRef,
+    conn: &Connection,
+    options: &CollectOptions,
+) -> Option<Vec<Hyperlink>> {
+    let hypertext = proxies.hypertext().await.ok()?;
+    let text = proxies.text().await.ok();
+    let count = match hypertext.get_nlinks().await {
+        Ok(count) => count,
+        Err(err) => {
+            error!("Error: Failed to count links of {}: {err}", address(object));
+            return None;
+        }
+    };
+
+    let mut links = Vec::new();
+    for index in 0..count {
+        let link = async {
+            let link = hypertext.get_link(index).await?;
+            let link = HyperlinkProxy::builder(conn)
+                .destination(link.name)?
+                .path(link.path)?
+                .build()
+                .await?;
+            futures::try_join!(link.start_index(), link.end_index(), link.get_uri(0))
+        };
+        let (start, end, uri) = match link.await {
+            Ok(link) => link,
+            Err(err) => {
+                error!(
+                    "Error: Failed to get link {index} of {}: {err}",
+                    address(object)
+                );
+                continue;
+            }
+        };
+        let extents = match &text {
+            Some(text) => text
+                .get_range_extents(start, end, CoordType::from(options.coordinates))
+                .await
+                .ok()
+                .map(Extents::new),
+            None => None,
+        };
+        links.push(Hyperlink {
+            start,
+            end,
+            uri,
+            extents,
+        });
+    }
+    Some(links)
+}
+
+/// Fetches the whole text of a node implementing the Text interface.
+///
+/// Nodes without the interface yield `None` silently; query failures are logged.
+async fn resolve_text(proxies: &Proxies<'_>, object: &ObjectRef) -> Option<String> {
+    let text = proxies.text().await.ok()?;
+    // An end offset of -1 stands for the end of the text.
+    match text.get_text(0, -1).await {
+        Ok(contents) => Some(contents),
+        Err(err) => {
+            error!(
+                "Error: Failed to get text from text interface for {}: {err}",
+                address(object)
+            );
+            None
+        }
+    }
+}
+
+/// Fetches the colors of the start of a node's text, including the defaults of the
+/// whole text, and their contrast.
+///
+/// Nodes without the Text interface or without both colors yield `None` silently; query
+/// failures are logged.
+async fn resolve_contrast(proxies: &Proxies<'_>, object: &ObjectRef) -> Option<TextContrast> {
+    let text = proxies.text().await.ok()?;
+    match text.get_attribute_run(0, true).await {
+        Ok((attributes, _, _)) => TextContrast::from_attributes(&attributes),
+        Err(err) => {
+            error!(
+                "Error: Failed to get text attributes for {}: {err}",
+                address(object)
+            );
+            None
+        }
+    }
+}
+
+/// Fetches the current value and range of a node implementing the Value interface.
+///
+/// Nodes without the interface yield `None` silently; query failures are logged.
+async fn resolve_value(proxies: &Proxies<'_>, object: &ObjectRef) -> Option<NodeValue> {
+    let value = proxies.value().await.ok()?;
+
+    let range = futures::try_join!(
+        value.current_value(),
+        value.minimum_value(),
+        value.maximum_value()
+    );
+    match range {
+        Ok((current, minimum, maximum)) => Some(NodeValue {
+            current,
+            minimum,
+            maximum,
+        }),
+        Err(err) => {
+            error!(
+                "Error: Failed to get value from value interface for {}: {err}",
+                address(object)
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::tests::node;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use zbus::names::UniqueName;
+    use zbus::zvariant::ObjectPath;
+
+    #[tokio::test]
+    async fn retry_succeeds_after_a_failure() {
+        let calls = Cell::new(0);
+        let (result, used) = retry(3, Duration::ZERO, || {
+            calls.set(calls.get() + 1);
+            let call = calls.get();
+            async move { if call == 1 { Err("busy") } else { Ok(call) } }
+        })
+        .await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(used, 1);
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_after_its_retries() {
+        let calls = Cell::new(0);
+        let (result, used) = retry(2, Duration::ZERO, || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>("busy") }
+        })
+        .await;
+        assert_eq!(result, Err("busy"));
+        assert_eq!(used, 2);
+        assert_eq!(calls.get(), 3);
+    }
+
+    /// A tree of nodes named after their object paths, each with the given state and
+    /// children.
+    #[derive(Default)]
+    struct FakeTree(HashMap<ObjectRef, (StateSet, Vec<ObjectRef>)>);
+
+    impl FakeTree {
+        /// Adds the node `name`, showing or else in the `state`, with the `children`.
+        fn add(&mut self, name: &str, state: Option<State>, children: &[String]) -> ObjectRef {
+            let states = StateSet::new(state.unwrap_or(State::Showing) | State::Showing);
+            let children = children.iter().map(|child| object(child)).collect();
+            self.0.insert(object(name), (states, children));
+            object(name)
+        }
+    }
+
+    fn object(name: &str) -> ObjectRef {
+        ObjectRef::new(
+            UniqueName::from_static_str_unchecked(":1.1"),
+            ObjectPath::try_from(format!("/{name}")).unwrap(),
+        )
+    }
+
+    impl Tree for FakeTree {
+        type Node = ObjectRef;
+
+        async fn children(&self, node: &ObjectRef, _: bool) -> Result<Vec<ObjectRef>, Error> {
+            Ok(self.0[node].1.clone())
+        }
+
+        async fn node(&self, child: &ObjectRef) -> Result<(ObjectRef, StateSet), Error> {
+            Ok((child.clone(), self.0[child].0))
+        }
+
+        async fn resolve(
+            &self,
+            _: &ObjectRef,
+            object: ObjectRef,
+            states: StateSet,
+            parent: Option<usize>,
+            depth: usize,
+            _: &CollectOptions,
+        ) -> Result<NodeInfo, Error> {
+            let name = object.path.as_str()[1..].to_string();
+            Ok(NodeInfo {
+                object,
+                parent,
+                depth,
+                states,
+                ..node(Role::Panel, &name, None)
+            })
+        }
+    }
+
+    /// Traverses `tree` from `root`, returning the collected nodes and the stats.
+    async fn collect(
+        tree: &FakeTree,
+        root: ObjectRef,
+        options: CollectOptions,
+    ) -> (Vec<NodeInfo>, CollectionStats) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let stats = traverse(tree, root, &options, &tx).await.unwrap();
+        drop(tx);
+        let mut nodes = Vec::new();
+        while let Some(node) = rx.recv().await {
+            nodes.push(node);
+        }
+        (nodes, stats)
+    }
+
+    fn names(nodes: &[NodeInfo]) -> Vec<&str> {
+        nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn leaves_out_defunct_children_and_their_subtrees() {
+        let mut tree = FakeTree::default();
+        tree.add("leaf", None, &[]);
+        tree.add("orphan", None, &[]);
+        tree.add("kept", None, &["leaf".into()]);
+        tree.add("defunct", Some(State::Defunct), &["orphan".into()]);
+        let root = tree.add("root", None, &["kept".into(), "defunct".into()]);
+
+        let (nodes, stats) = collect(&tree, root, CollectOptions::default()).await;
+        assert_eq!(names(&nodes), ["kept", "leaf"]);
+        assert_eq!(nodes[1].parent, Some(0));
+        assert_eq!(stats.skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_children_and_records_the_total() {
+        let mut tree = FakeTree::default();
+        let leaves: Vec<String> = (0..1000).map(|leaf| format!("leaf{leaf}")).collect();
+        for leaf in &leaves {
+            tree.add(leaf, None, &[]);
+        }
+        tree.add("wide", None, &leaves);
+        tree.add("narrow", None, &leaves[..10]);
+        let root = tree.add("root", None, &["wide".into(), "narrow".into()]);
+        let options = CollectOptions {
+            max_children: Some(10),
+            ..CollectOptions::default()
+        };
+
+        let (nodes, _) = collect(&tree, root, options).await;
+        assert_eq!(nodes.len(), 22);
+        let truncated = |name| {
+            let node = nodes.iter().find(|node| node.name == name).unwrap();
+            node.truncated_children
+        };
+        assert_eq!(truncated("wide"), Some(1000));
+        assert_eq!(truncated("narrow"), None);
+        assert_eq!(truncated("leaf0"), None);
+        let wide = nodes.iter().position(|node| node.name == "wide");
+        assert_eq!(nodes.iter().filter(|node| node.parent == wide).count(), 10);
+    }
+}
@@ -0,0 +1,740 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::proxy::accessible::ObjectRefExt;
+use atspi::proxy::action::ActionProxy;
+use atspi::{AtspiError, CoordType, ObjectRef};
+use atspi_proxies::proxy_ext::ProxyExt;
+use egui::Pos2;
+use log::{debug, error, info, warn};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::watch;
+use tokio_stream::StreamExt;
+use zbus::Connection;
+
+use crate::apps::{Application, is_own, list_applications, toolkit_info};
+use crate::cli::Args;
+use crate::collect::{
+    CollectOptions, accessible_at_point, ancestry, resolve_detached, stream_children,
+};
+use crate::command::{Command, HitTarget};
+use crate::export::{write_dot, write_folded, write_json, write_svg};
+use crate::gui::{GuiMessage, GuiSender};
+use crate::mirror::Mirror;
+use crate::node::{BothExtents, Extents, address};
+use crate::session::{Frame, Recorder};
+
+/// Where `W` records the session when `--record` is not given.
+const DEFAULT_SESSION_PATH: &str = "atspi-session.ndjson";
+
+/// How often the list of running applications is re-read while cycling.
+const APPLICATION_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Most nodes handed to the GUI in one batch while a tree is being collected.
+const BATCH_SIZE: usize = 64;
+/// Longest time collected nodes are held back before being handed to the GUI.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One of the collections started by the collector.
+pub struct Collection {
+    /// Identifies the collection; later collections have higher numbers.
+    pub number: u64,
+    /// The number of the newest collection started.
+    pub newest: watch::Receiver<u64>,
+}
+
+impl Collection {
+    /// Waits until a newer collection has started.
+    async fn superseded(&mut self) {
+        let number = self.number;
+        // The collector going away supersedes every collection, too.
+        let _ = self.newest.wait_for(|&newest| newest != number).await;
+    }
+}
+
+/// Collects the tree below `root`, handing nodes to the GUI in batches as they are
+/// resolved, then exports, records, and mirrors the finished tree if requested.
+///
+/// The collection's number lets the GUI tell its batches apart from those of collections
+/// started earlier. Once a newer collection starts the traversal is cancelled, which is
+/// logged, and nothing is exported or recorded.
+pub async fn collect_and_send(
+    root: ObjectRef,
+    mut collection: Collection,
+    conn: &Arc<Connection>,
+    tx: &GuiSender,
+    args: &Args,
+    options: CollectOptions,
+    sinks: &Sinks,
+) {
+    // A failure here only means the application does not implement the interface.
+    let toolkit = match toolkit_info(&root, conn).await {
+        Ok(toolkit) => Some(toolkit),
+        Err(err) => {
+            debug!("No toolkit information for {}: {err}", address(&root));
+            None
+        }
+    };
+
+    if tx.send(GuiMessage::Toolkit(toolkit.clone())).is_err() {
+        debug!("GUI has shut down, dropping toolkit information");
+        return;
+    }
+
+    match ancestry(root.clone(), conn).await {
+        Ok(crumbs) => {
+            if tx
+                .send(GuiMessage::Ancestry(collection.number, crumbs))
+                .is_err()
+            {
+                debug!("GUI has shut down, dropping ancestry");
+                return;
+            }
+        }
+        Err(err) => debug!("Error resolving ancestry of {}: {err}", address(&root)),
+    }
+
+    let (mut stream, traversal) = stream_children(root.clone(), conn.clone(), options);
+    let mut nodes = Vec::new();
+    let mut sent = 0;
+    let mut last_batch = Instant::now();
+    loop {
+        let node = tokio::select! {
+            node = stream.next() => node,
+            _ = collection.superseded() => None,
+        };
+        let Some(node) = node else {
+            break;
+        };
+        nodes.push(node);
+        if nodes.len() - sent >= BATCH_SIZE || last_batch.elapsed() >= BATCH_INTERVAL {
+            let batch = nodes[sent..].to_vec();
+            if tx
+                .send(GuiMessage::Nodes(collection.number, batch))
+                .is_err()
+            {
+                debug!("GUI has shut down, dropping collected nodes");
+                return;
+            }
+            sent = nodes.len();
+            last_batch = Instant::now();
+        }
+    }
+
+    // Dropping the stream stops a traversal that is still running.
+    drop(stream);
+    let stats = match traversal.await {
+        Ok(Ok(stats)) if stats.cancelled => {
+            info!("{stats}");
+            return;
+        }
+        Ok(Ok(stats)) => {
+            info!("{stats}");
+            if stats.focused > 1 {
+                warn!(
+                    "{} nodes below {} claim focus at once",
+                    stats.focused,
+                    address(&root)
+                );
+            }
+            for (inner, outer) in &stats.nested_interactive {
+                info!("Interactive {inner} is nested in {outer}");
+            }
+            if stats.duplicate_sibling_names > 0 {
+                warn!(
+                    "{} nodes below {} share their name with a sibling",
+                    stats.duplicate_sibling_names,
+                    address(&root)
+                );
+            }
+            if stats.offscreen_focusable > 0 {
+                warn!(
+                    "{} focusable nodes below {} lie off screen",
+                    stats.offscreen_focusable,
+                    address(&root)
+                );
+            }
+            if let Some(min_percent) = args.min_name_coverage
+                && stats.name_coverage.is_below(min_percent)
+            {
+                error!(
+                    "Error: {} of {} is below the minimum of {min_percent}%",
+                    stats.name_coverage,
+                    address(&root)
+                );
+                sinks.below_coverage.store(true, Ordering::Relaxed);
+            }
+            stats
+        }
+        // The root vanished before it could be collected, e.g. a document replaced right
+        // after loading; what is on screen is as current as it gets until the next one.
+        Ok(Err(err)) if err.is_stale() => {
+            warn!(
+                "{} went away before it could be collected, keeping the previous tree: {err}",
+                address(&root)
+            );
+            return;
+        }
+        // Only an unreachable root fails a collection, so whatever is on screen is gone.
+        Ok(Err(err)) => {
+            error!("Error collecting children of {}: {err}", address(&root));
+            let newest = *collection.newest.borrow() == collection.number;
+            if newest && tx.send(GuiMessage::Clear).is_err() {
+                debug!("GUI has shut down, dropping clear");
+            }
+            return;
+        }
+        Err(err) => {
+            error!("Collection of {} panicked: {err}", address(&root));
+            return;
+        }
+    };
+
+    if sent < nodes.len() {
+        let batch = nodes[sent..].to_vec();
+        if tx
+            .send(GuiMessage::Nodes(collection.number, batch))
+            .is_err()
+        {
+            debug!("GUI has shut down, dropping collected nodes");
+            return;
+        }
+    }
+    if tx
+        .send(GuiMessage::TreeFinished(collection.number))
+        .is_err()
+    {
+        debug!("GUI has shut down, dropping finished collection");
+    }
+
+    if let Some(path) = &args.export_json
+        && let Err(err) = write_json(&nodes, toolkit.as_ref(), path, args.anonymize)
+    {
+        error!("Error exporting JSON: {err}");
+    }
+
+    if let Some(path) = &args.export_svg
+        && let Err(err) = write_svg(&nodes, path, args.anonymize)
+    {
+        error!("Error exporting SVG: {err}");
+    }
+
+    if let Some(path) = &args.export_dot
+        && let Err(err) = write_dot(&nodes, path, args.anonymize)
+    {
+        error!("Error exporting DOT: {err}");
+    }
+
+    if let Some(path) = &args.export_folded
+        && let Some(profile) = &stats.profile
+        && let Err(err) = write_folded(&nodes, &root, profile, path, args.anonymize)
+    {
+        error!("Error exporting folded stacks: {err}");
+    }
+
+    if sinks.recorder.is_none() && sinks.mirror.is_none() {
+        return;
+    }
+    let frame = Frame::now(nodes, toolkit);
+    if let Some(mirror) = &sinks.mirror
+        && let Err(err) = mirror.publish(&frame)
+    {
+        error!("Error mirroring tree: {err}");
+    }
+    if let Some(recorder) = &sinks.recorder
+        && let Err(err) = recorder.record(frame)
+    {
+        error!("Error recording session frame: {err}");
+    }
+}
+
+/// Where finished trees are sent besides the GUI.
+#[derive(Debug, Clone, Default)]
+pub struct Sinks {
+    /// The session file trees are recorded to while recording.
+    pub recorder: Option<Arc<Recorder>>,
+    /// Remote viewers, with `--serve`.
+    pub mirror: Option<Arc<Mirror>>,
+    /// Set once a tree falls below `--min-name-coverage`, failing a headless run.
+    pub below_coverage: Arc<AtomicBool>,
+}
+
+/// Decides what to collect and serves commands from the GUI and the event watcher.
+pub struct Collector {
+    conn: Arc<Connection>,
+    tx: GuiSender,
+    args: Arc<Args>,
+    /// Running applications, only maintained with `--applications`.
+    applications: Vec<Application>,
+    /// Names of applications left out of `applications`.
+    excluded_applications: Vec<String>,
+    application_index: usize,
+    /// The most recently collected root, re-collected when the region changes.
+    last_root: Option<ObjectRef>,
+    /// Roots zoomed out of, most recent last, returned to by zooming out.
+    root_history: Vec<ObjectRef>,
+    /// Number of collections started so far, identifying the newest one; running
+    /// collections watch it to stop once superseded.
+    collections: watch::Sender<u64>,
+    /// Screen region collections are limited to, initially `--region`.
+    region: Option<Extents>,
+    /// What collections resolve, apart from `region`.
+    options: CollectOptions,
+    /// Where collected trees are recorded and mirrored.
+    sinks: Sinks,
+    /// Set while nothing worth showing has focus; collections are paused meanwhile.
+    idle: bool,
+    /// The document stayed on with `--first-only`, until released.
+    latched: Option<ObjectRef>,
+}
+
+impl Collector {
+    pub fn new(
+        conn: Arc<Connection>,
+        tx: GuiSender,
+        args: Arc<Args>,
+        options: CollectOptions,
+        excluded_applications: Vec<String>,
+    ) -> Self {
+        Self {
+            conn,
+            tx,
+            applications: Vec::new(),
+            excluded_applications,
+            application_index: 0,
+            last_root: None,
+            root_history: Vec::new(),
+            collections: watch::Sender::new(0),
+            region: args.region,
+            options,
+            sinks: Sinks::default(),
+            idle: false,
+            latched: None,
+            args,
+        }
+    }
+
+    /// Set once a collected tree falls below `--min-name-coverage`, and never reset.
+    pub fn below_coverage(&self) -> Arc<AtomicBool> {
+        self.sinks.below_coverage.clone()
+    }
+
+    /// Streams every finished tree to the viewers of `mirror`, if any.
+    pub fn with_mirror(mut self, mirror: Option<Arc<Mirror>>) -> Self {
+        self.sinks.mirror = mirror;
+        self
+    }
+
+    /// Serves `commands` until the channel closes.
+    pub async fn run(mut self, mut commands: UnboundedReceiver<Command>) {
+        if self.args.record.is_some() {
+            self.set_recording(true);
+        }
+        if let Some(root) = self.args.root.clone() {
+            self.collect(root);
+        }
+
+        let mut refresh = tokio::time::interval(APPLICATION_REFRESH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = refresh.tick(), if self.args.applications && !self.idle => {
+                    self.refresh_applications().await
+                }
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        debug!("Command channel closed, stopping collector");
+                        return;
+                    };
+                    self.handle(command).await;
+                }
+            }
+        }
+    }
+
+    /// Collects the tree below `root` in the background.
+    fn collect(&mut self, root: ObjectRef) {
+        self.collect_after(root, Duration::ZERO);
+    }
+
+    /// Collects the tree below a document that just loaded once `--settle-delay` has
+    /// passed, as applications often keep populating it for a moment after announcing it.
+    fn collect_settled(&mut self, root: ObjectRef) {
+        let delay = Duration::from_secs_f64(self.args.settle_delay);
+        self.collect_after(root, delay);
+    }
+
+    /// Collects the tree below `root` in the background after `delay`, unless a newer
+    /// collection starts meanwhile.
+    fn collect_after(&mut self, root: ObjectRef, delay: Duration) {
+        self.last_root = Some(root.clone());
+        self.collections
+            .send_modify(|collections| *collections += 1);
+        let mut collection = Collection {
+            number: *self.collections.borrow(),
+            newest: self.collections.subscribe(),
+        };
+        let (conn, tx, args) = (self.conn.clone(), self.tx.clone(), self.args.clone());
+        let sinks = self.sinks.clone();
+        let options = CollectOptions {
+            region: self.region,
+            ..self.options.clone()
+        };
+        tokio::spawn(async move {
+            if !delay.is_zero() {
+                tokio::select! {
+                    () = tokio::time::sleep(delay) => {}
+                    () = collection.superseded() => {
+                        debug!("Superseded while waiting for {} to settle", address(&root));
+                        return;
+                    }
+                }
+            }
+            collect_and_send(root, collection, &conn, &tx, &args, options, &sinks).await;
+        });
+    }
+
+    async fn handle(&mut self, command: Command) {
+        match command {
+            Command::NextApplication if !self.applications.is_empty() => {
+                self.application_index = (self.application_index + 1) % self.applications.len();
+                self.show_application().await;
+            }
+            Command::PreviousApplication if !self.applications.is_empty() => {
+                let len = self.applications.len();
+                self.application_index = (self.application_index + len - 1) % len;
+                self.show_application().await;
+            }
+            Command::NextApplication | Command::PreviousApplication => {}
+            Command::SetRecording(recording) => self.set_recording(recording),
+            Command::SetIdle(idle) => self.set_idle(idle).await,
+            // Whatever loaded is picked up by the re-collection when resuming.
+            Command::DocumentLoaded(_) if self.idle => {}
+            // Stay on the subtree zoomed into.
+            Command::DocumentLoaded(_) if !self.root_history.is_empty() => {
+                if let Some(root) = self.last_root.clone() {
+                    self.collect_settled(root);
+                }
+            }
+            Command::DocumentLoaded(document) => {
+                if self.args.applications {
+                    // Only re-collect when the document belongs to the application shown.
+                    if let Some(application) = self.applications.get(self.application_index)
+                        && application.object.name == document.name
+                    {
+                        self.collect_settled(application.object.clone());
+                    }
+                } else if self.args.first_only {
+                    match &self.latched {
+                        Some(latched) if *latched != document => {
+                            debug!("Ignoring {} loaded after the first", address(&document));
+                        }
+                        _ => {
+                            self.latched = Some(document.clone());
+                            self.collect_settled(document);
+                        }
+                    }
+                } else {
+                    self.collect_settled(self.args.root.clone().unwrap_or(document));
+                }
+            }
+            Command::ReleaseDocument => {
+                if let Some(document) = self.latched.take() {
+                    info!(
+                        "Released {}, waiting for the next document",
+                        address(&document)
+                    );
+                }
+            }
+            Command::ZoomTo(object) => {
+                info!("Zooming to {}", address(&object));
+                self.root_history.extend(self.last_root.take());
+                self.collect(object);
+            }
+            Command::ShowRoot(object) => {
+                info!("Showing {}", address(&object));
+                match self.root_history.iter().rposition(|root| *root == object) {
+                    Some(index) => self.root_history.truncate(index),
+                    None => self.root_history.extend(self.last_root.take()),
+                }
+                self.collect(object);
+            }
+            Command::ZoomOut => match self.root_history.pop() {
+                Some(root) => {
+                    info!("Zooming out to {}", address(&root));
+                    self.collect(root);
+                }
+                None => debug!("Not zoomed in, nothing to zoom out to"),
+            },
+            Command::QueryBothExtents(object) => {
+                let (conn, tx) = (self.conn.clone(), self.tx.clone());
+                tokio::spawn(async move {
+                    send_both_extents(object, &conn, &tx).await;
+                });
+            }
+            Command::QueryInterfaces(object) => {
+                let (conn, tx) = (self.conn.clone(), self.tx.clone());
+                tokio::spawn(async move {
+                    send_interfaces(object, &conn, &tx).await;
+                });
+            }
+            Command::SetRegion(region) => {
+                self.region = region;
+                if let Some(root) = self.last_root.clone() {
+                    self.collect(root);
+                }
+            }
+            Command::HitTest { target, point } => {
+                let (conn, tx, args) = (self.conn.clone(), self.tx.clone(), self.args.clone());
+                let root = self.last_root.clone();
+                tokio::spawn(async move {
+                    send_hit_test(target, point, root, &conn, &tx, &args).await;
+                });
+            }
+            Command::QueryActions(object) => {
+                let (conn, tx) = (self.conn.clone(), self.tx.clone());
+                tokio::spawn(async move {
+                    send_actions(object, &conn, &tx).await;
+                });
+            }
+            Command::InvokeAction {
+                object,
+                index,
+                name,
+            } => {
+                let conn = self.conn.clone();
+                tokio::spawn(async move {
+                    invoke_action(object, index, name, &conn).await;
+                });
+            }
+        }
+    }
+
+    fn set_recording(&mut self, recording: bool) {
+        if !recording {
+            if self.sinks.recorder.take().is_some() {
+                info!("Stopped recording");
+            }
+            return;
+        }
+        let path = self
+            .args
+            .record
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SESSION_PATH.into());
+        match Recorder::open(&path, self.args.anonymize) {
+            Ok(recorder) => {
+                info!("Recording session to {}", path.display());
+                self.sinks.recorder = Some(Arc::new(recorder));
+            }
+            Err(err) => error!("Error opening session {}: {err}", path.display()),
+        }
+    }
+
+    async fn set_idle(&mut self, idle: bool) {
+        if idle == self.idle {
+            return;
+        }
+        self.idle = idle;
+        info!("{} collection", if idle { "Pausing" } else { "Resuming" });
+        if self.tx.send(GuiMessage::Idle(idle)).is_err() {
+            debug!("GUI has shut down, dropping idle state");
+            return;
+        }
+        if !idle && let Some(root) = self.last_root.clone() {
+            self.collect(root);
+        }
+    }
+
+    /// Re-reads the running applications, keeping the cursor on the same application if
+    /// it is still running.
+    async fn refresh_applications(&mut self) {
+        let current = self.applications.get(self.application_index).cloned();
+        let applications = match list_applications(&self.conn).await {
+            Ok(applications) => applications,
+            Err(err) => {
+                error!("Error listing applications: {err}");
+                return;
+            }
+        };
+        self.applications.clear();
+        for application in applications {
+            if self.excluded_applications.contains(&application.name) {
+                continue;
+            }
+            // The overlay never shows its own tree, whatever it is called.
+            match is_own(&application.object, &self.conn).await {
+                Ok(false) => self.applications.push(application),
+                Ok(true) => {}
+                Err(err) => debug!("Skipping application {}: {err}", application.name),
+            }
+        }
+
+        match current.and_then(|app| self.applications.iter().position(|a| *a == app)) {
+            Some(position) => self.application_index = position,
+            None => {
+                self.application_index = 0;
+                self.show_application().await;
+            }
+        }
+    }
+
+    /// Announces the current application to the GUI and collects its tree.
+    async fn show_application(&mut self) {
+        let Some(application) = self.applications.get(self.application_index) else {
+            return;
+        };
+
+        info!("Showing application {}", application.name);
+        self.root_history.clear();
+        if self
+            .tx
+            .send(GuiMessage::Application(application.name.clone()))
+            .is_err()
+        {
+            debug!("GUI has shut down, dropping application switch");
+            return;
+        }
+        self.collect(application.object.clone());
+    }
+}
+
+/// Asks AT-SPI which node below `root` is at `point` and hands it to the GUI.
+///
+/// The GUI falls back to scanning its cached rects when no node is returned, so failures
+/// here are only logged at debug level.
+async fn send_hit_test(
+    target: HitTarget,
+    point: Pos2,
+    root: Option<ObjectRef>,
+    conn: &Connection,
+    tx: &GuiSender,
+    args: &Args,
+) {
+    let (x, y) = (point.x.round() as i32, point.y.round() as i32);
+    let object = match root {
+        Some(root) => {
+            match accessible_at_point(root, x, y, args.coordinates.unwrap_or_default(), conn).await
+            {
+                Ok(object) => object,
+                Err(err) => {
+                    debug!("Hit test at ({x}, {y}) failed: {err}");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut node = None;
+    if let Some(object) = object {
+        let resolved = match object.clone().into_accessible_proxy(conn).await {
+            Ok(proxy) => resolve_detached(&proxy, object, &CollectOptions::from(args)).await,
+            Err(err) => Err(err.into()),
+        };
+        match resolved {
+            Ok(resolved) => node = Some(resolved),
+            Err(err) => debug!("Error resolving hit test result: {err}"),
+        }
+    }
+
+    if tx
+        .send(GuiMessage::HitTest {
+            target,
+            point,
+            node,
+        })
+        .is_err()
+    {
+        debug!("GUI has shut down, dropping hit test result");
+    }
+}
+
+/// Queries the screen and window extents of `object` at once and hands them to the GUI.
+async fn send_both_extents(object: ObjectRef, conn: &Connection, tx: &GuiSender) {
+    let extents = async {
+        let proxy = object.clone().into_accessible_proxy(conn).await?;
+        let component = proxy.proxies().await?.component().await?;
+        let (screen, window) = futures::try_join!(
+            component.get_extents(CoordType::Screen),
+            component.get_extents(CoordType::Window)
+        )?;
+        Ok::<_, AtspiError>(BothExtents {
+            screen: Extents::new(screen),
+            window: Extents::new(window),
+        })
+    };
+
+    match extents.await {
+        Ok(extents) => {
+            if tx.send(GuiMessage::BothExtents(object, extents)).is_err() {
+                debug!("GUI has shut down, dropping extents");
+            }
+        }
+        Err(err) => error!("Error querying extents of {}: {err}", address(&object)),
+    }
+}
+
+/// Queries which interfaces `object` implements and hands them to the GUI.
+async fn send_interfaces(object: ObjectRef, conn: &Connection, tx: &GuiSender) {
+    let interfaces = match object.as_accessible_proxy(conn).await {
+        Ok(proxy) => proxy.get_interfaces().await,
+        Err(err) => Err(err),
+    };
+
+    match interfaces {
+        Ok(interfaces) => {
+            let interfaces = interfaces.iter().collect();
+            if tx.send(GuiMessage::Interfaces(object, interfaces)).is_err() {
+                debug!("GUI has shut down, dropping interfaces");
+            }
+        }
+        Err(err) => error!("Error querying interfaces of {}: {err}", address(&object)),
+    }
+}
+
+async fn action_proxy<'a>(
+    object: &ObjectRef,
+    conn: &'a Connection,
+) -> Result<ActionProxy<'a>, AtspiError> {
+    let proxy = object.clone().into_accessible_proxy(conn).await?;
+    proxy.proxies().await?.action().await
+}
+
+/// Lists the actions `object` offers and hands them to the GUI.
+async fn send_actions(object: ObjectRef, conn: &Connection, tx: &GuiSender) {
+    let actions = match action_proxy(&object, conn).await {
+        Ok(action) => action.get_actions().await.map_err(AtspiError::from),
+        Err(err) => Err(err),
+    };
+
+    match actions {
+        Ok(actions) => {
+            if tx.send(GuiMessage::Actions(object, actions)).is_err() {
+                debug!("GUI has shut down, dropping actions");
+            }
+        }
+        Err(err) => error!("Error querying actions of {}: {err}", address(&object)),
+    }
+}
+
+/// Performs the action at `index` on `object` and logs the outcome.
+async fn invoke_action(object: ObjectRef, index: i32, name: String, conn: &Connection) {
+    info!("Invoking action '{name}' on {}", address(&object));
+    let result = match action_proxy(&object, conn).await {
+        Ok(action) => action.do_action(index).await.map_err(AtspiError::from),
+        Err(err) => Err(err),
+    };
+
+    match result {
+        Ok(true) => info!("Action '{name}' on {} succeeded", address(&object)),
+        Ok(false) => warn!("Action '{name}' on {} reported failure", address(&object)),
+        Err(err) => error!(
+            "Error invoking action '{name}' on {}: {err}",
+            address(&object)
+        ),
+    }
+}
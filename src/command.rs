@@ -0,0 +1,58 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::ObjectRef;
+use egui::Pos2;
+
+use crate::node::Extents;
+
+/// What a hit test result is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitTarget {
+    /// Select the node for the inspector.
+    Select,
+    /// Highlight the node in hover mode.
+    Hover,
+}
+
+/// Requests sent to the collector side, mostly from GUI hotkeys.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Show the next running application.
+    NextApplication,
+    /// Show the previous running application.
+    PreviousApplication,
+    /// A document finished loading somewhere on the bus.
+    DocumentLoaded(ObjectRef),
+    /// With `--first-only`, let the next loaded document replace the one latched onto.
+    ReleaseDocument,
+    /// Start or stop recording collected trees to the session file.
+    SetRecording(bool),
+    /// Pause collecting while nothing worth showing has focus, or resume.
+    SetIdle(bool),
+    /// Collect the subtree of a node instead, remembering the current root. Used both to
+    /// drill down and to climb up through the breadcrumb.
+    ZoomTo(ObjectRef),
+    /// Go back to the root collected before the last zoom.
+    ZoomOut,
+    /// Collect a root stepped to by undoing or redoing in the GUI: stepping back to a
+    /// root zoomed out of cuts the zoom history back to it, so `ZoomOut` continues from
+    /// there, while any other root is zoomed to.
+    ShowRoot(ObjectRef),
+    /// Limit collection to a screen region, or lift the limit, and re-collect.
+    SetRegion(Option<Extents>),
+    /// Find the accessible at a point in screen coordinates through AT-SPI.
+    HitTest { target: HitTarget, point: Pos2 },
+    /// Fetch the screen and window extents of a node for the inspector.
+    QueryBothExtents(ObjectRef),
+    /// List the interfaces implemented by a node for the inspector.
+    QueryInterfaces(ObjectRef),
+    /// List the actions a node offers for the inspector.
+    QueryActions(ObjectRef),
+    /// Perform the action at `index` on a node. This acts on the target application.
+    InvokeAction {
+        object: ObjectRef,
+        index: i32,
+        name: String,
+    },
+}
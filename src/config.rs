@@ -0,0 +1,574 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::Role;
+use clap::ValueEnum;
+use egui::{Color32, Pos2, Rect};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::keys::KeyBindings;
+use crate::lint::LintRule;
+use crate::node::{Extents, SignificantState};
+use crate::render::LinePattern;
+
+/// Settings persisted between runs in a TOML file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Radius in pixels of the corners of node boxes; square when 0.
+    pub corner_radius: f32,
+    /// Boxes narrower or shorter than this many pixels on the overlay, after calibration
+    /// scaling, are not drawn outside lint mode; all are drawn when 0.
+    pub min_box_size: f32,
+    /// Skip drawing nodes whose box matches their parent's within this many pixels, such
+    /// as layout wrappers; they are still collected and exported. Off when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_wrappers: Option<i32>,
+    /// Keep the outlines of selected and flagged nodes still instead of pulsing, and
+    /// show new trees without animating the transition.
+    pub reduce_motion: bool,
+    /// Seconds over which boxes move to their place in a newly collected tree, while new
+    /// nodes fade in and removed ones fade out; shown right away when 0.
+    pub transition_duration: f32,
+    pub calibration: Calibration,
+    pub hit_test: HitTestStrategy,
+    pub lint: LintConfig,
+    pub traversal: TraversalConfig,
+    /// Where node labels are drawn relative to their box.
+    pub label_placement: LabelPlacement,
+    /// Font size of node labels in logical points, scaled for the display; 11 when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_size: Option<f32>,
+    /// Most labels drawn at once, picked by `label_priority`; every box is still drawn.
+    /// Unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_labels: Option<usize>,
+    /// Which labels are kept when there are more than `max_labels`.
+    pub label_priority: Priority,
+    /// Most nodes drawn per frame, picked by `render_priority`, to keep frames quick on
+    /// huge trees; the rest are counted on the overlay. Unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render_budget: Option<usize>,
+    /// Which nodes are drawn when there are more than `render_budget`.
+    pub render_priority: Priority,
+    /// What the color of node markers shows.
+    pub marker_colors: MarkerColors,
+    /// How node boxes are drawn, unless `--style` says otherwise.
+    pub box_style: BoxStyle,
+    pub patterns: PatternConfig,
+    pub partial_extents: PartialExtentsConfig,
+    /// Keys rebound from their defaults, by action.
+    pub keys: KeyBindings,
+    /// Where `D` captures trees to; the working directory when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_dir: Option<PathBuf>,
+    /// Names of applications `--applications` skips, such as the terminal it runs in.
+    pub exclude_applications: Vec<String>,
+    /// Overlay background, e.g. `#00ff00` for chroma keying; transparent when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<HexColor>,
+}
+
+/// Where node labels are anchored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LabelPlacement {
+    /// Centered inside the box.
+    Inside,
+    /// Just above the top edge, clear of the box's content.
+    #[default]
+    Above,
+    /// In the top-left corner inside the box, next to the marker.
+    Corner,
+}
+
+/// Which nodes are kept under a limit such as `max_labels` or `render_budget`, after the
+/// focused node and the selected one, which always are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Priority {
+    /// The ones with the largest boxes, which are easiest to make out and whose labels
+    /// have the most room.
+    #[default]
+    Largest,
+    /// The ones closest to the collection root.
+    Shallowest,
+    /// The ones collected first.
+    Collection,
+}
+
+/// How the box of every drawn node is painted, in its marker color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BoxStyle {
+    /// A translucent fill.
+    Filled,
+    /// A thin outline, which keeps the content of the box readable.
+    #[default]
+    Outline,
+    /// A translucent fill inside a solid outline.
+    Both,
+}
+
+impl BoxStyle {
+    pub fn fills(self) -> bool {
+        matches!(self, Self::Filled | Self::Both)
+    }
+
+    pub fn outlines(self) -> bool {
+        matches!(self, Self::Outline | Self::Both)
+    }
+}
+
+/// What the color of node markers is derived from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MarkerColors {
+    /// Every marker is red.
+    #[default]
+    Uniform,
+    /// Each role gets its own hue.
+    Role,
+    /// The node's most significant state; see `node::significant_state`.
+    State,
+    /// Each application gets its own hue, which roles vary the lightness of, so trees of
+    /// several applications stay apart.
+    Application,
+}
+
+impl MarkerColors {
+    /// The scheme after this one when cycling with `K`.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Uniform => Self::Role,
+            Self::Role => Self::State,
+            Self::State => Self::Application,
+            Self::Application => Self::Uniform,
+        }
+    }
+}
+
+/// Line patterns and hatching that set nodes in a significant state apart without relying
+/// on color, for colorblind users.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternConfig {
+    /// Outline every node in a significant state in its pattern, whatever the marker
+    /// colors show.
+    pub enabled: bool,
+    pub focused: StatePattern,
+    pub selected: StatePattern,
+    pub checked: StatePattern,
+    pub disabled: StatePattern,
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        let line = |line| StatePattern {
+            line,
+            hatched: false,
+        };
+        Self {
+            enabled: false,
+            focused: line(LinePattern::Solid),
+            selected: line(LinePattern::Dashed),
+            checked: line(LinePattern::Dotted),
+            disabled: StatePattern {
+                line: LinePattern::Dotted,
+                hatched: true,
+            },
+        }
+    }
+}
+
+impl PatternConfig {
+    /// The pattern of nodes whose most significant state is `state`.
+    pub fn get(&self, state: SignificantState) -> StatePattern {
+        match state {
+            SignificantState::Focused => self.focused,
+            SignificantState::Selected => self.selected,
+            SignificantState::Checked => self.checked,
+            SignificantState::Disabled => self.disabled,
+        }
+    }
+}
+
+/// How the outline of nodes in one state is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatePattern {
+    pub line: LinePattern,
+    /// Also fill the box with diagonal lines.
+    pub hatched: bool,
+}
+
+/// How extents with only a usable position or only a usable size are drawn, instead of
+/// as degenerate rects.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartialExtentsConfig {
+    pub position_only: PositionOnly,
+    pub size_only: SizeOnly,
+    /// Side in pixels of the marker drawn for position-only extents.
+    pub marker_size: f32,
+}
+
+impl Default for PartialExtentsConfig {
+    fn default() -> Self {
+        Self {
+            position_only: PositionOnly::default(),
+            size_only: SizeOnly::default(),
+            marker_size: 8.0,
+        }
+    }
+}
+
+/// What to draw for extents with a position but no area.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PositionOnly {
+    /// A square of `marker_size` at the position.
+    #[default]
+    Marker,
+    Skip,
+}
+
+/// What to draw for extents with an area but a placeholder position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeOnly {
+    #[default]
+    Skip,
+    /// A box of the reported size in the middle of the screen.
+    Center,
+}
+
+impl PartialExtentsConfig {
+    /// Where `extents` is drawn on an overlay covering `screen`, in overlay coordinates;
+    /// `None` if it is not drawn at all.
+    pub fn place(
+        &self,
+        extents: &Extents,
+        calibration: &Calibration,
+        screen: Rect,
+    ) -> Option<Rect> {
+        match (extents.has_position(), extents.has_size()) {
+            (true, true) => Some(calibration.apply(extents.rect())),
+            (true, false) => (self.position_only == PositionOnly::Marker).then(|| {
+                let min = calibration.apply_pos(Pos2::new(extents.x as f32, extents.y as f32));
+                Rect::from_min_size(min, egui::vec2(self.marker_size, self.marker_size))
+            }),
+            (false, true) => (self.size_only == SizeOnly::Center).then(|| {
+                let size = extents.rect().size() * calibration.scale;
+                Rect::from_center_size(screen.center(), size)
+            }),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Role-aware pruning of the traversal and optional per-node queries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraversalConfig {
+    /// Names of roles, e.g. `table`, whose children are not traversed.
+    pub stop_roles: Vec<String>,
+    /// Only traverse this many children of any one node, e.g. of a list with thousands of
+    /// items; unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_children: Option<usize>,
+    /// Query the rows, columns, and cells of nodes implementing the Table interface.
+    pub tables: bool,
+    /// Most cells resolved per table, as each one costs several queries.
+    pub max_table_cells: usize,
+    /// Query the links embedded in the text of nodes implementing the Hypertext interface.
+    pub hyperlinks: bool,
+    /// Query which children of nodes implementing the Selection interface are selected.
+    pub selections: bool,
+    /// Query the text colors of nodes implementing the Text interface to check their
+    /// contrast; one more query per text node.
+    pub contrast: bool,
+}
+
+impl Default for TraversalConfig {
+    fn default() -> Self {
+        Self {
+            stop_roles: Vec::new(),
+            max_children: None,
+            tables: false,
+            max_table_cells: 400,
+            hyperlinks: false,
+            selections: false,
+            contrast: false,
+        }
+    }
+}
+
+impl TraversalConfig {
+    /// How many cells are resolved per table, or `None` if tables are not queried.
+    pub fn table_cells(&self) -> Option<usize> {
+        self.tables.then_some(self.max_table_cells)
+    }
+
+    /// Resolves `stop_roles` to roles, failing on names AT-SPI does not define.
+    pub fn stop_roles(&self) -> Result<Vec<Role>, Error> {
+        self.stop_roles
+            .iter()
+            .map(|name| {
+                (0..=u8::MAX as u32)
+                    .filter_map(|value| Role::try_from(value).ok())
+                    .find(|role| role.name() == name)
+                    .ok_or_else(|| {
+                        Error::Invalid(format!("unknown role '{name}' in traversal.stop_roles"))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// A color written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HexColor(pub Color32);
+
+impl FromStr for HexColor {
+    type Err = String;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !matches!(digits.len(), 6 | 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("'{hex}' is not a color like #rrggbb or #rrggbbaa"));
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+        let alpha = if digits.len() == 8 { channel(6) } else { 255 };
+        Ok(Self(Color32::from_rgba_unmultiplied(
+            channel(0),
+            channel(2),
+            channel(4),
+            alpha,
+        )))
+    }
+}
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        hex.parse()
+    }
+}
+
+impl From<HexColor> for String {
+    fn from(color: HexColor) -> Self {
+        let [r, g, b, a] = color.0.to_srgba_unmultiplied();
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+/// The minimum target size recommended by WCAG 2.2, in pixels.
+pub const DEFAULT_MIN_TARGET_SIZE: f32 = 24.0;
+
+/// The minimum contrast of normal text in WCAG 2 level AA.
+pub const DEFAULT_MIN_CONTRAST: f64 = 4.5;
+
+/// Which heuristics lint mode checks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub rules: Vec<LintRule>,
+    /// Smallest width and height in pixels an interactive node may have before
+    /// `tiny-target` flags it.
+    pub min_target_size: f32,
+    /// Lowest contrast ratio between the text and background colors of a node before
+    /// `low-contrast` flags it.
+    pub min_contrast: f64,
+    /// The bounds of the screen in AT-SPI coordinates, which `offscreen-focusable` checks
+    /// against; found from the monitors at startup rather than configured.
+    #[serde(skip)]
+    pub screen: Option<Extents>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            rules: LintRule::ALL.to_vec(),
+            min_target_size: DEFAULT_MIN_TARGET_SIZE,
+            min_contrast: DEFAULT_MIN_CONTRAST,
+            screen: None,
+        }
+    }
+}
+
+/// How the node under the pointer is found when clicking or hovering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HitTestStrategy {
+    /// Ask AT-SPI through `get_accessible_at_point`, which also handles overlapping and
+    /// transformed content; falls back to `Rects` when no node answers.
+    #[default]
+    AtPoint,
+    /// Pick the smallest cached rect containing the pointer.
+    Rects,
+}
+
+/// Corrects a systematic misalignment between reported extents and the overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Calibration {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub scale: f32,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Calibration {
+    /// Maps a point in reported screen coordinates to overlay coordinates.
+    pub fn apply_pos(&self, pos: Pos2) -> Pos2 {
+        Pos2::new(
+            pos.x * self.scale + self.offset_x,
+            pos.y * self.scale + self.offset_y,
+        )
+    }
+
+    /// Maps a rect in reported screen coordinates to overlay coordinates.
+    pub fn apply(&self, rect: Rect) -> Rect {
+        Rect::from_min_max(self.apply_pos(rect.min), self.apply_pos(rect.max))
+    }
+
+    /// Maps a point in overlay coordinates back to reported screen coordinates.
+    pub fn invert_pos(&self, pos: Pos2) -> Pos2 {
+        Pos2::new(
+            (pos.x - self.offset_x) / self.scale,
+            (pos.y - self.offset_y) / self.scale,
+        )
+    }
+
+    /// This calibration applied to extents relative to `origin`, given in reported
+    /// screen coordinates, so that `origin` maps to where the screen's top-left did.
+    pub fn relative_to(&self, origin: Pos2) -> Self {
+        Self {
+            offset_x: self.offset_x - origin.x * self.scale,
+            offset_y: self.offset_y - origin.y * self.scale,
+            scale: self.scale,
+        }
+    }
+
+    /// Maps a rect in overlay coordinates back to reported screen coordinates.
+    pub fn invert(&self, rect: Rect) -> Rect {
+        Rect::from_min_max(self.invert_pos(rect.min), self.invert_pos(rect.max))
+    }
+}
+
+/// Returns `$XDG_CONFIG_HOME/atspi-tree-visualizer/config.toml`, falling back to
+/// `~/.config` when `XDG_CONFIG_HOME` is unset.
+pub fn default_path() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_default();
+    base.join("atspi-tree-visualizer").join("config.toml")
+}
+
+impl Config {
+    /// Loads the config at `path`, using defaults if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|err| {
+                Error::Invalid(format!("Invalid config file {}: {err}", path.display()))
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(Error::Invalid(format!(
+                "Failed to read config file {}: {err}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Writes the config to `path`, creating its parent directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1000.0, 800.0));
+
+    fn calibration() -> Calibration {
+        Calibration {
+            offset_x: 10.0,
+            offset_y: 20.0,
+            scale: 2.0,
+        }
+    }
+
+    #[test]
+    fn skips_missing_extents() {
+        let config = PartialExtentsConfig::default();
+        let extents = Extents::new((i32::MIN, i32::MIN, 0, 0));
+        assert_eq!(config.place(&extents, &calibration(), SCREEN), None);
+    }
+
+    #[test]
+    fn marks_zero_sized_extents() {
+        let mut config = PartialExtentsConfig::default();
+        let extents = Extents::new((5, 5, 0, 0));
+        assert_eq!(
+            config.place(&extents, &calibration(), SCREEN),
+            Some(Rect::from_min_size(
+                Pos2::new(20.0, 30.0),
+                egui::vec2(8.0, 8.0)
+            ))
+        );
+        config.position_only = PositionOnly::Skip;
+        assert_eq!(config.place(&extents, &calibration(), SCREEN), None);
+    }
+
+    #[test]
+    fn centers_extents_with_a_placeholder_position() {
+        let mut config = PartialExtentsConfig::default();
+        let extents = Extents::new((-1, -1, 50, 30));
+        assert_eq!(config.place(&extents, &calibration(), SCREEN), None);
+        config.size_only = SizeOnly::Center;
+        assert_eq!(
+            config.place(&extents, &calibration(), SCREEN),
+            Some(Rect::from_center_size(
+                Pos2::new(500.0, 400.0),
+                egui::vec2(100.0, 60.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn calibrates_full_extents() {
+        let config = PartialExtentsConfig::default();
+        let extents = Extents::new((-5, 5, 50, 30));
+        assert_eq!(
+            config.place(&extents, &calibration(), SCREEN),
+            Some(Rect::from_min_max(
+                Pos2::new(0.0, 30.0),
+                Pos2::new(100.0, 90.0)
+            ))
+        );
+    }
+}
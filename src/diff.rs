@@ -0,0 +1,119 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::State;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::node::NodeInfo;
+
+/// States that follow the user's interaction rather than the application's structure,
+/// so they are ignored when comparing against a baseline.
+const TRANSIENT_STATES: [State; 3] = [State::Focused, State::Active, State::Selected];
+
+/// The differences between a baseline tree and a current one that matter for
+/// `--diff-baseline`.
+///
+/// Bus names and object paths change between runs, so nodes are matched by their
+/// structural path instead, see `structural_paths`. Extents are ignored, as they depend
+/// on where the window happens to be; a matched node differs if its description or its
+/// states, apart from `TRANSIENT_STATES`, do.
+#[derive(Debug, Clone, Default)]
+pub struct TreeDiff {
+    /// Paths of nodes only in the current tree.
+    pub added: Vec<String>,
+    /// Paths of nodes only in the baseline.
+    pub removed: Vec<String>,
+    /// Paths of matched nodes, each with what differs.
+    pub changed: Vec<(String, String)>,
+}
+
+impl TreeDiff {
+    /// Compares `current` against `baseline`, listing differences in collection order.
+    pub fn between(baseline: &[NodeInfo], current: &[NodeInfo]) -> Self {
+        let baseline_paths = structural_paths(baseline);
+        let current_paths = structural_paths(current);
+        let by_path: HashMap<&str, &NodeInfo> = baseline_paths
+            .iter()
+            .map(String::as_str)
+            .zip(baseline)
+            .collect();
+
+        let mut diff = Self::default();
+        for (path, node) in current_paths.iter().zip(current) {
+            let Some(old) = by_path.get(path.as_str()) else {
+                diff.added.push(path.clone());
+                continue;
+            };
+            let mut differences = Vec::new();
+            if old.description != node.description {
+                differences.push(format!(
+                    "description {:?} -> {:?}",
+                    old.description, node.description
+                ));
+            }
+            let (mut old_states, mut states) = (old.states, node.states);
+            for state in TRANSIENT_STATES {
+                old_states.remove(state);
+                states.remove(state);
+            }
+            if old_states != states {
+                differences.push(format!("states {old_states:?} -> {states:?}"));
+            }
+            if !differences.is_empty() {
+                diff.changed.push((path.clone(), differences.join(", ")));
+            }
+        }
+        let current_paths: HashSet<&str> = current_paths.iter().map(String::as_str).collect();
+        diff.removed = baseline_paths
+            .iter()
+            .filter(|path| !current_paths.contains(path.as_str()))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// The number of differences: added, removed, and changed nodes.
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+impl fmt::Display for TreeDiff {
+    /// One line per difference, prefixed `+`, `-`, or `~` like a diff.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in &self.added {
+            writeln!(f, "+ {path}")?;
+        }
+        for path in &self.removed {
+            writeln!(f, "- {path}")?;
+        }
+        for (path, differences) in &self.changed {
+            writeln!(f, "~ {path}: {differences}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies each node by the labels of its ancestors and itself, e.g.
+/// `frame: Editor/push button: OK`, which stays the same between runs of an application.
+/// Siblings with the same label are told apart by a `#n` suffix, counting from 2.
+pub fn structural_paths(nodes: &[NodeInfo]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::with_capacity(nodes.len());
+    // How often each label has been seen below each parent, `None` being the root.
+    let mut seen: HashMap<(Option<usize>, String), usize> = HashMap::new();
+    for node in nodes {
+        let label = node.label();
+        let count = seen.entry((node.parent, label.clone())).or_default();
+        *count += 1;
+        let segment = match *count {
+            1 => label,
+            n => format!("{label}#{n}"),
+        };
+        paths.push(match node.parent {
+            Some(parent) => format!("{}/{segment}", paths[parent]),
+            None => segment,
+        });
+    }
+    paths
+}
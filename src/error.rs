@@ -0,0 +1,76 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::AtspiError;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A failure of the visualizer, by where it happened, so callers can react to each kind,
+/// e.g. reconnect on connection errors but only skip a node on traversal errors.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The accessibility bus could not be reached or set up.
+    #[error("Failed to connect to accessibility bus {bus}: {source}")]
+    Connection {
+        /// The address connected to, or `session` for the one announced on the session bus.
+        bus: String,
+        #[source]
+        source: AtspiError,
+    },
+    /// Querying an accessible failed, e.g. because its application quit meanwhile.
+    #[error(transparent)]
+    Traversal(#[from] AtspiError),
+    /// A tree could not be written to an export.
+    #[error("Failed to write {}: {source}", path.display())]
+    Render {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The overlay window could not be opened or failed while running. Kept as its
+    /// message, as `eframe`'s errors cannot be sent between threads.
+    #[error("Failed to run the overlay: {0}")]
+    Gui(String),
+    /// A file or argument given to the visualizer cannot be used.
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<zbus::Error> for Error {
+    fn from(err: zbus::Error) -> Self {
+        Self::Traversal(err.into())
+    }
+}
+
+/// Names of the `org.freedesktop.DBus.Error` errors answering a query on an object that
+/// no longer exists, e.g. a document replaced by the time its load event is handled.
+const STALE_OBJECT_ERRORS: [&str; 4] = [
+    "UnknownObject",
+    "UnknownMethod",
+    "ServiceUnknown",
+    "NameHasNoOwner",
+];
+
+impl Error {
+    /// Whether a query failed because the queried object is gone, in which case asking
+    /// again cannot succeed.
+    pub fn is_stale(&self) -> bool {
+        // `atspi` keeps zbus errors only as their debug output, which names the D-Bus error
+        // either in full or, for standard errors, by its last component.
+        match self {
+            Self::Traversal(AtspiError::Zbus(message)) => STALE_OBJECT_ERRORS
+                .iter()
+                .any(|name| message.contains(name)),
+            _ => false,
+        }
+    }
+
+    /// Wraps an I/O error writing the export at `path`.
+    pub fn render(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self::Render {
+            path: path.into(),
+            source,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use log::{Level, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::status::StatusLine;
+
+/// How many recent errors are kept for the on-screen error panel.
+const ERROR_LOG_CAPACITY: usize = 100;
+
+/// A logged error or warning.
+#[derive(Debug, Clone)]
+pub struct ErrorEntry {
+    pub time: Instant,
+    pub level: Level,
+    pub message: String,
+}
+
+/// A bounded ring buffer of the most recent errors and warnings.
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    entries: Mutex<VecDeque<ErrorEntry>>,
+}
+
+impl ErrorLog {
+    fn push(&self, entry: ErrorEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == ERROR_LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Returns the recorded entries, newest first.
+    pub fn recent(&self) -> Vec<ErrorEntry> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    /// How many entries were recorded within the last `window`.
+    pub fn count_since(&self, window: Duration) -> usize {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .rev()
+            .take_while(|entry| entry.time.elapsed() < window)
+            .count()
+    }
+}
+
+/// Forwards records to `env_logger` while capturing errors and warnings for the GUI,
+/// printing them above the status line if one is shown.
+struct CapturingLogger {
+    inner: env_logger::Logger,
+    errors: Arc<ErrorLog>,
+    status: Arc<StatusLine>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= Level::Warn && self.inner.matches(record) {
+            self.errors.push(ErrorEntry {
+                time: Instant::now(),
+                level: record.level(),
+                message: record.args().to_string(),
+            });
+        }
+        self.status.around(|| self.inner.log(record));
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs the global logger, returning the buffer errors are captured into and the
+/// status line records are kept clear of.
+pub fn init() -> (Arc<ErrorLog>, Arc<StatusLine>) {
+    let inner =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let errors = Arc::new(ErrorLog::default());
+    let status = Arc::new(StatusLine::default());
+
+    log::set_max_level(inner.filter());
+    log::set_boxed_logger(Box::new(CapturingLogger {
+        inner,
+        errors: errors.clone(),
+        status: status.clone(),
+    }))
+    .expect("logger is only initialized once");
+
+    (errors, status)
+}
@@ -0,0 +1,280 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::ObjectRef;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::apps::ToolkitInfo;
+use crate::error::Error;
+use crate::node::{Hyperlink, NodeInfo, address, fnv1a, group_hue, top_level_ancestor};
+use crate::stats::{NameCoverage, TraversalProfile};
+
+/// The document written by `write_json`.
+#[derive(Serialize)]
+struct Export<'a> {
+    /// The toolkit of the application the tree was collected from, if it reported one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    toolkit: Option<&'a ToolkitInfo>,
+    name_coverage: NameCoverage,
+    nodes: &'a [NodeInfo],
+}
+
+/// The part of a document written by `write_json` that `read_json` needs.
+#[derive(Deserialize)]
+struct Dump {
+    nodes: Vec<NodeInfo>,
+}
+
+/// Replaces non-empty `text` with a placeholder derived from its hash, which is stable
+/// across runs, so anonymized dumps of the same tree stay comparable.
+///
+/// Empty strings are kept as-is since a missing name is itself useful a11y information.
+fn redact(kind: &str, text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("{kind}-{:08x}", fnv1a(text) as u32)
+    }
+}
+
+/// Returns a copy of `nodes` with names, descriptions, the contents of editable text, and
+/// link targets redacted.
+///
+/// Structure, roles, and geometry are preserved.
+pub fn anonymize(nodes: &[NodeInfo]) -> Vec<NodeInfo> {
+    nodes
+        .iter()
+        .map(|node| NodeInfo {
+            name: redact("name", &node.name),
+            description: redact("description", &node.description),
+            text: node.text.as_deref().map(|text| redact("text", text)),
+            hyperlinks: node.hyperlinks.as_ref().map(|links| {
+                links
+                    .iter()
+                    .map(|link| Hyperlink {
+                        uri: redact("uri", &link.uri),
+                        ..link.clone()
+                    })
+                    .collect()
+            }),
+            ..node.clone()
+        })
+        .collect()
+}
+
+/// Escapes `text` for use in SVG text content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders every node with extents as an outlined box labelled with its role and name,
+/// colored by its top-level container like the overlay's hulls.
+///
+/// The document is cropped to the bounding box of all nodes, with coordinates kept in
+/// screen space through the `viewBox`.
+pub fn render_svg(nodes: &[NodeInfo]) -> String {
+    let rects: Vec<_> = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| Some((index, node.extents?)))
+        .filter(|(_, extents)| extents.width > 0 && extents.height > 0)
+        .collect();
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0, 0, 0, 0);
+    if let Some((_, first)) = rects.first() {
+        (min_x, min_y) = (first.x, first.y);
+        (max_x, max_y) = (first.x + first.width, first.y + first.height);
+    }
+    for (_, extents) in &rects {
+        min_x = min_x.min(extents.x);
+        min_y = min_y.min(extents.y);
+        max_x = max_x.max(extents.x + extents.width);
+        max_y = max_y.max(extents.y + extents.height);
+    }
+    let (width, height) = (max_x - min_x, max_y - min_y);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"{min_x} {min_y} {width} {height}\" font-family=\"sans-serif\" font-size=\"10\">\n"
+    );
+    for (index, extents) in rects {
+        let node = &nodes[index];
+        let hue = (group_hue(top_level_ancestor(nodes, index)) * 360.0).round();
+        let label = escape_xml(&node.label());
+        // Writing to a String cannot fail.
+        let _ = writeln!(
+            svg,
+            "  <g><title>{label}</title>\
+             <rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" \
+             fill=\"hsl({hue},80%,50%)\" fill-opacity=\"0.1\" stroke=\"hsl({hue},80%,40%)\"/>\
+             <text x=\"{tx}\" y=\"{ty}\" fill=\"hsl({hue},80%,30%)\">{label}</text></g>",
+            x = extents.x,
+            y = extents.y,
+            w = extents.width,
+            h = extents.height,
+            tx = extents.x + 2,
+            ty = extents.y + 10,
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes `nodes` rendered by `render_svg` to `path`.
+pub fn write_svg(nodes: &[NodeInfo], path: &Path, anonymized: bool) -> Result<(), Error> {
+    let svg = if anonymized {
+        render_svg(&anonymize(nodes))
+    } else {
+        render_svg(nodes)
+    };
+    fs::write(path, svg).map_err(|err| Error::render(path, err))
+}
+
+/// Quotes `text` as a DOT string.
+fn quote_dot(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// Renders the hierarchy of `nodes` as a GraphViz digraph, one vertex per node labelled
+/// with its role and name and one edge per parent-child relationship.
+///
+/// Vertices are identified by their accessible address, which stays stable between
+/// collections of the same application.
+pub fn render_dot(nodes: &[NodeInfo]) -> String {
+    let ids: Vec<String> = nodes
+        .iter()
+        .map(|node| quote_dot(&address(&node.object)))
+        .collect();
+
+    let mut dot = String::from("digraph accessibility_tree {\n  node [shape=box];\n");
+    for (index, node) in nodes.iter().enumerate() {
+        let _ = writeln!(
+            dot,
+            "  {} [label={}];",
+            ids[index],
+            quote_dot(&node.label())
+        );
+    }
+    for (index, node) in nodes.iter().enumerate() {
+        if let Some(parent) = node.parent {
+            let _ = writeln!(dot, "  {} -> {};", ids[parent], ids[index]);
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes `nodes` rendered by `render_dot` to `path`.
+pub fn write_dot(nodes: &[NodeInfo], path: &Path, anonymized: bool) -> Result<(), Error> {
+    let dot = if anonymized {
+        render_dot(&anonymize(nodes))
+    } else {
+        render_dot(nodes)
+    };
+    fs::write(path, dot).map_err(|err| Error::render(path, err))
+}
+
+/// Makes `label` safe as a frame of a folded stack, in which `;` separates frames and
+/// each line ends in a space and the count.
+fn folded_frame(label: &str) -> String {
+    label.replace(';', ",").replace(['\n', '\r'], " ")
+}
+
+/// Renders the time `profile` records for each node as folded stacks, one line per node
+/// with the labels of its ancestors and itself followed by its own cost in microseconds,
+/// so flamegraphs show which subtrees dominate the traversal. The stacks start at a frame
+/// for the collection `root`, which carries the cost of listing its children.
+pub fn render_folded(nodes: &[NodeInfo], root: &ObjectRef, profile: &TraversalProfile) -> String {
+    let root = folded_frame(&address(root));
+    let mut stacks: Vec<String> = Vec::with_capacity(nodes.len());
+    let mut folded = format!("{root} {}\n", profile.root.as_micros());
+    for (index, node) in nodes.iter().enumerate() {
+        let parent = node.parent.map_or(&root, |parent| &stacks[parent]);
+        let stack = format!("{parent};{}", folded_frame(&node.label()));
+        let cost = profile.nodes.get(index).copied().unwrap_or_default();
+        let _ = writeln!(folded, "{stack} {}", cost.as_micros());
+        stacks.push(stack);
+    }
+    folded
+}
+
+/// Writes `nodes` and their costs rendered by `render_folded` to `path`.
+pub fn write_folded(
+    nodes: &[NodeInfo],
+    root: &ObjectRef,
+    profile: &TraversalProfile,
+    path: &Path,
+    anonymized: bool,
+) -> Result<(), Error> {
+    let folded = if anonymized {
+        render_folded(&anonymize(nodes), root, profile)
+    } else {
+        render_folded(nodes, root, profile)
+    };
+    fs::write(path, folded).map_err(|err| Error::render(path, err))
+}
+
+/// Serializes `nodes`, their name coverage, and the `toolkit` they come from as JSON to
+/// `path`, or to stdout if `path` is `-`.
+pub fn write_json(
+    nodes: &[NodeInfo],
+    toolkit: Option<&ToolkitInfo>,
+    path: &Path,
+    anonymized: bool,
+) -> Result<(), Error> {
+    let nodes = if anonymized {
+        Cow::Owned(anonymize(nodes))
+    } else {
+        Cow::Borrowed(nodes)
+    };
+    let export = Export {
+        toolkit,
+        name_coverage: NameCoverage::of(&nodes),
+        nodes: &nodes,
+    };
+
+    let write = || -> io::Result<()> {
+        let mut writer: Box<dyn Write> = if path == Path::new("-") {
+            Box::new(io::stdout().lock())
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+        serde_json::to_writer_pretty(&mut writer, &export)?;
+        writeln!(writer)?;
+        writer.flush()
+    };
+    write().map_err(|err| Error::render(path, err))
+}
+
+/// Reads the nodes of a JSON document written by `write_json` from `path`.
+pub fn read_json(path: &Path) -> Result<Vec<NodeInfo>, Error> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        Error::Invalid(format!(
+            "Failed to read JSON dump {}: {err}",
+            path.display()
+        ))
+    })?;
+    let dump: Dump = serde_json::from_str(&contents)
+        .map_err(|err| Error::Invalid(format!("Invalid JSON dump {}: {err}", path.display())))?;
+    Ok(dump.nodes)
+}
@@ -0,0 +1,138 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+/// What to do with the tree on screen for a message of some collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The message is from an outdated collection, or repeats what is already known.
+    Ignore,
+    /// Append the batch to the tree being collected.
+    Extend,
+    /// The batch starts a newer collection; it replaces the tree on screen.
+    Replace,
+    /// The tree being collected has all of its nodes.
+    Complete,
+    /// A newer collection finished without handing over any nodes, so it found an
+    /// empty tree.
+    Empty,
+}
+
+/// Tracks the newest collection the tree on screen is assembled from, deciding how each
+/// batch and completion folds into it. Collections may arrive out of order, e.g. when a
+/// slow one finishes after a newer one has started.
+#[derive(Debug, Default)]
+pub struct Assembly {
+    /// The newest collection seen, and whether it is still running.
+    collection: Option<(u64, bool)>,
+}
+
+impl Assembly {
+    /// Folds in a batch of nodes from `collection`.
+    pub fn batch(&mut self, collection: u64) -> Step {
+        match self.collection {
+            Some((newest, _)) if collection < newest => Step::Ignore,
+            Some((newest, true)) if collection == newest => Step::Extend,
+            _ => {
+                self.collection = Some((collection, true));
+                Step::Replace
+            }
+        }
+    }
+
+    /// Folds in `collection` having handed over all of its nodes.
+    pub fn finished(&mut self, collection: u64) -> Step {
+        match self.collection {
+            Some((newest, _)) if collection < newest => Step::Ignore,
+            Some((newest, running)) if collection == newest => {
+                if running {
+                    self.collection = Some((collection, false));
+                    Step::Complete
+                } else {
+                    Step::Ignore
+                }
+            }
+            _ => {
+                self.collection = Some((collection, false));
+                Step::Empty
+            }
+        }
+    }
+
+    /// Whether a collection is still handing over nodes.
+    pub fn running(&self) -> bool {
+        matches!(self.collection, Some((_, true)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A message of a collection: a batch of node ids, or `None` for its completion.
+    type Message = (u64, Option<Vec<u32>>);
+
+    /// Folds `messages` into a tree the way the GUI does, returning the tree and each
+    /// finished one.
+    fn assemble(messages: Vec<Message>) -> (Vec<u32>, Vec<Vec<u32>>) {
+        let mut assembly = Assembly::default();
+        let (mut tree, mut finished) = (Vec::new(), Vec::new());
+        for (collection, batch) in messages {
+            match batch {
+                Some(batch) => match assembly.batch(collection) {
+                    Step::Extend => tree.extend(batch),
+                    Step::Replace => tree = batch,
+                    step => assert_eq!(step, Step::Ignore),
+                },
+                None => match assembly.finished(collection) {
+                    Step::Complete => finished.push(tree.clone()),
+                    Step::Empty => {
+                        tree.clear();
+                        finished.push(Vec::new());
+                    }
+                    step => assert_eq!(step, Step::Ignore),
+                },
+            }
+        }
+        (tree, finished)
+    }
+
+    #[test]
+    fn assembles_batches_in_order() {
+        let (tree, finished) = assemble(vec![
+            (1, Some(vec![1, 2])),
+            (1, Some(vec![3])),
+            (1, None),
+            (2, Some(vec![4])),
+            (2, None),
+        ]);
+        assert_eq!(tree, [4]);
+        assert_eq!(finished, [vec![1, 2, 3], vec![4]]);
+    }
+
+    #[test]
+    fn drops_batches_of_outdated_collections() {
+        let (tree, finished) = assemble(vec![
+            (2, Some(vec![4])),
+            (1, Some(vec![1])),
+            (2, Some(vec![5])),
+            (1, None),
+            (2, None),
+        ]);
+        assert_eq!(tree, [4, 5]);
+        assert_eq!(finished, [vec![4, 5]]);
+    }
+
+    #[test]
+    fn replaces_the_tree_with_an_empty_newer_one() {
+        let (tree, finished) = assemble(vec![(1, Some(vec![1])), (1, None), (2, None)]);
+        assert!(tree.is_empty());
+        assert_eq!(finished, [vec![1], vec![]]);
+    }
+
+    #[test]
+    fn ignores_repeated_completions() {
+        let (tree, finished) = assemble(vec![(1, Some(vec![1])), (1, None), (1, None)]);
+        assert_eq!(tree, [1]);
+        assert_eq!(finished, [vec![1]]);
+    }
+}
@@ -0,0 +1,355 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use eframe::egui;
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use super::GuiMessage;
+use crate::node::NodeInfo;
+
+/// The nodes of the newest collection that the GUI has not taken yet.
+#[derive(Debug)]
+struct PendingTree {
+    collection: u64,
+    nodes: Vec<NodeInfo>,
+    /// Whether the collection has handed over all of its nodes.
+    finished: bool,
+}
+
+/// Adds `nodes` of `collection` to the `pending` tree, latest wins: a newer collection
+/// replaces the nodes of an older one the GUI never got to, and batches of older
+/// collections are dropped.
+fn stash(pending: &mut Option<PendingTree>, collection: u64, nodes: Vec<NodeInfo>, finished: bool) {
+    match pending {
+        Some(tree) if collection < tree.collection => {}
+        Some(tree) if collection == tree.collection => {
+            tree.nodes.extend(nodes);
+            tree.finished |= finished;
+        }
+        _ => {
+            *pending = Some(PendingTree {
+                collection,
+                nodes,
+                finished,
+            })
+        }
+    }
+}
+
+/// The messages the pending tree is handed over as: its nodes, if any, then whether it
+/// is finished.
+fn unstash(tree: PendingTree) -> impl Iterator<Item = GuiMessage> {
+    let nodes = (!tree.nodes.is_empty()).then_some(GuiMessage::Nodes(tree.collection, tree.nodes));
+    let finished = tree
+        .finished
+        .then_some(GuiMessage::TreeFinished(tree.collection));
+    nodes.into_iter().chain(finished)
+}
+
+/// Whether the `queued` message is outdated by `next`, so only `next` needs handing over.
+///
+/// Every message but collected nodes, which go through `PendingTree`, either sets some
+/// state of the GUI, where only the latest value matters, or answers a query about one
+/// node, where only the latest answer about that node does. So at most one message of
+/// each kind, or per inspected node, is ever queued.
+fn supersedes(next: &GuiMessage, queued: &GuiMessage) -> bool {
+    use GuiMessage::*;
+    match (next, queued) {
+        // An ancestry arriving late for an older collection must not hide the newer one.
+        (Ancestry(next, _), Ancestry(queued, _)) => next >= queued,
+        (BothExtents(next, _), BothExtents(queued, _))
+        | (Interfaces(next, _), Interfaces(queued, _))
+        | (Actions(next, _), Actions(queued, _)) => next == queued,
+        (HitTest { target: next, .. }, HitTest { target: queued, .. }) => next == queued,
+        (next, queued) => mem::discriminant(next) == mem::discriminant(queued),
+    }
+}
+
+/// Adds `message` to the `queue`, dropping whatever queued message it supersedes.
+fn enqueue(queue: &mut VecDeque<GuiMessage>, message: GuiMessage) {
+    queue.retain(|queued| !supersedes(&message, queued));
+    queue.push_back(message);
+}
+
+/// What the senders have handed over and the receiver has not taken yet.
+#[derive(Default)]
+struct Inbox {
+    messages: VecDeque<GuiMessage>,
+    tree: Option<PendingTree>,
+    /// Senders still alive; once there are none, nothing more can arrive.
+    senders: usize,
+    /// Whether the receiver is gone.
+    closed: bool,
+}
+
+impl Inbox {
+    fn is_empty(&self) -> bool {
+        self.messages.is_empty() && self.tree.is_none()
+    }
+}
+
+/// The state both halves of the channel share.
+#[derive(Default)]
+struct Shared {
+    inbox: Mutex<Inbox>,
+    /// Wakes `GuiReceiver::recv` when something arrives or the last sender goes away.
+    arrived: Notify,
+}
+
+/// Returned when sending to a GUI that has shut down.
+#[derive(Debug)]
+pub struct Closed;
+
+/// The sending half of the GUI's messages, which wakes the GUI when they arrive: egui
+/// only runs `update`, which takes the messages, when something asks for a repaint.
+///
+/// Sending never waits for the GUI, and what is sent stays bounded however slow, idle,
+/// or hidden the GUI is: collected nodes go to a slot holding only the newest collection,
+/// so stale trees are missed rather than holding up the collector, and every other
+/// message replaces the one it supersedes, see `supersedes`.
+pub struct GuiSender {
+    shared: Arc<Shared>,
+    /// The GUI's context; `None` with `--headless`, where nothing is drawn.
+    ctx: Option<egui::Context>,
+}
+
+impl GuiSender {
+    /// Sends `message`, failing only once the GUI has shut down.
+    pub fn send(&self, message: GuiMessage) -> Result<(), Closed> {
+        let mut inbox = self.shared.inbox.lock().unwrap();
+        if inbox.closed {
+            return Err(Closed);
+        }
+        // Once woken, the GUI takes everything; until then it needs no reminder.
+        let was_empty = inbox.is_empty();
+        match message {
+            GuiMessage::Nodes(collection, nodes) => {
+                stash(&mut inbox.tree, collection, nodes, false)
+            }
+            GuiMessage::TreeFinished(collection) => {
+                stash(&mut inbox.tree, collection, Vec::new(), true)
+            }
+            // Nodes still pending are from before whatever made the tree go away.
+            GuiMessage::Clear => {
+                inbox.tree = None;
+                enqueue(&mut inbox.messages, GuiMessage::Clear);
+            }
+            message => enqueue(&mut inbox.messages, message),
+        }
+        drop(inbox);
+        self.shared.arrived.notify_one();
+        if was_empty && let Some(ctx) = &self.ctx {
+            ctx.request_repaint();
+        }
+        Ok(())
+    }
+}
+
+impl Clone for GuiSender {
+    fn clone(&self) -> Self {
+        self.shared.inbox.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+            ctx: self.ctx.clone(),
+        }
+    }
+}
+
+impl Drop for GuiSender {
+    fn drop(&mut self) {
+        self.shared.inbox.lock().unwrap().senders -= 1;
+        self.shared.arrived.notify_one();
+    }
+}
+
+/// The receiving half of the GUI's messages.
+pub struct GuiReceiver {
+    shared: Arc<Shared>,
+    /// Messages of the pending tree taken from the slot but not handed over yet.
+    ready: VecDeque<GuiMessage>,
+}
+
+impl GuiReceiver {
+    /// Takes the next message, if any. Queued messages come before the pending tree, as
+    /// they were sent ahead of its latest nodes.
+    pub fn try_recv(&mut self) -> Option<GuiMessage> {
+        let mut inbox = self.shared.inbox.lock().unwrap();
+        if let Some(message) = inbox.messages.pop_front() {
+            return Some(message);
+        }
+        if self.ready.is_empty()
+            && let Some(tree) = inbox.tree.take()
+        {
+            self.ready.extend(unstash(tree));
+        }
+        self.ready.pop_front()
+    }
+
+    /// Waits for the next queued message, skipping collected trees, which only
+    /// `try_recv` hands over; `None` once every sender is gone.
+    pub async fn recv(&mut self) -> Option<GuiMessage> {
+        loop {
+            {
+                let mut inbox = self.shared.inbox.lock().unwrap();
+                inbox.tree = None;
+                if let Some(message) = inbox.messages.pop_front() {
+                    return Some(message);
+                }
+                if inbox.senders == 0 {
+                    return None;
+                }
+            }
+            self.shared.arrived.notified().await;
+        }
+    }
+}
+
+impl Drop for GuiReceiver {
+    fn drop(&mut self) {
+        self.shared.inbox.lock().unwrap().closed = true;
+    }
+}
+
+/// Creates the channel the GUI with the context `ctx` receives its messages through.
+pub fn channel(ctx: Option<egui::Context>) -> (GuiSender, GuiReceiver) {
+    let shared = Arc::new(Shared::default());
+    shared.inbox.lock().unwrap().senders = 1;
+    let sender = GuiSender {
+        shared: shared.clone(),
+        ctx,
+    };
+    let receiver = GuiReceiver {
+        shared,
+        ready: VecDeque::new(),
+    };
+    (sender, receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::tests::node;
+    use atspi::Role;
+
+    fn nodes(names: &[&str]) -> Vec<NodeInfo> {
+        names
+            .iter()
+            .map(|name| node(Role::Panel, name, None))
+            .collect()
+    }
+
+    /// Every message `receiver` has, summarized as e.g. `nodes 2 [a, b]` or `finished 2`.
+    fn drain(receiver: &mut GuiReceiver) -> Vec<String> {
+        std::iter::from_fn(|| receiver.try_recv())
+            .map(|message| match message {
+                GuiMessage::Nodes(collection, nodes) => {
+                    let names: Vec<_> = nodes.iter().map(|node| node.name.as_str()).collect();
+                    format!("nodes {collection} [{}]", names.join(", "))
+                }
+                GuiMessage::TreeFinished(collection) => format!("finished {collection}"),
+                GuiMessage::Clear => "clear".into(),
+                GuiMessage::Idle(idle) => format!("idle {idle}"),
+                GuiMessage::Notice(notice) => format!("notice {notice}"),
+                GuiMessage::Ancestry(collection, _) => format!("ancestry {collection}"),
+                _ => "other".into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stashes_batches_of_the_newest_collection() {
+        let mut pending = None;
+        stash(&mut pending, 1, nodes(&["a"]), false);
+        stash(&mut pending, 1, nodes(&["b"]), false);
+        let tree = pending.as_ref().unwrap();
+        assert_eq!(
+            (tree.collection, tree.nodes.len(), tree.finished),
+            (1, 2, false)
+        );
+
+        stash(&mut pending, 2, nodes(&["c"]), false);
+        stash(&mut pending, 1, nodes(&["d"]), true);
+        stash(&mut pending, 2, Vec::new(), true);
+        let tree = pending.unwrap();
+        assert_eq!(
+            (tree.collection, tree.nodes.len(), tree.finished),
+            (2, 1, true)
+        );
+        assert_eq!(tree.nodes[0].name, "c");
+    }
+
+    #[test]
+    fn unstashes_nodes_before_completion() {
+        let (sender, mut receiver) = channel(None);
+        sender
+            .send(GuiMessage::Nodes(1, nodes(&["a", "b"])))
+            .unwrap();
+        sender.send(GuiMessage::TreeFinished(1)).unwrap();
+        assert_eq!(drain(&mut receiver), ["nodes 1 [a, b]", "finished 1"]);
+
+        // A collection that found nothing only finishes.
+        sender.send(GuiMessage::TreeFinished(2)).unwrap();
+        assert_eq!(drain(&mut receiver), ["finished 2"]);
+    }
+
+    #[test]
+    fn hands_over_only_the_latest_tree() {
+        let (sender, mut receiver) = channel(None);
+        sender.send(GuiMessage::Nodes(1, nodes(&["a"]))).unwrap();
+        sender.send(GuiMessage::TreeFinished(1)).unwrap();
+        sender.send(GuiMessage::Nodes(2, nodes(&["b"]))).unwrap();
+        sender.send(GuiMessage::Nodes(1, nodes(&["late"]))).unwrap();
+        sender.send(GuiMessage::Nodes(2, nodes(&["c"]))).unwrap();
+        assert_eq!(drain(&mut receiver), ["nodes 2 [b, c]"]);
+    }
+
+    #[test]
+    fn drops_pending_nodes_when_cleared() {
+        let (sender, mut receiver) = channel(None);
+        sender.send(GuiMessage::Nodes(1, nodes(&["a"]))).unwrap();
+        sender.send(GuiMessage::Clear).unwrap();
+        sender.send(GuiMessage::Nodes(2, nodes(&["b"]))).unwrap();
+        assert_eq!(drain(&mut receiver), ["clear", "nodes 2 [b]"]);
+    }
+
+    #[test]
+    fn keeps_only_the_latest_message_of_each_kind() {
+        let (sender, mut receiver) = channel(None);
+        for _ in 0..1000 {
+            sender.send(GuiMessage::Idle(true)).unwrap();
+            sender.send(GuiMessage::Notice("busy".into())).unwrap();
+        }
+        sender.send(GuiMessage::Idle(false)).unwrap();
+        sender.send(GuiMessage::Ancestry(3, Vec::new())).unwrap();
+        sender.send(GuiMessage::Ancestry(2, Vec::new())).unwrap();
+        assert_eq!(
+            drain(&mut receiver),
+            ["notice busy", "idle false", "ancestry 3", "ancestry 2"]
+        );
+    }
+
+    #[test]
+    fn fails_once_the_receiver_is_gone() {
+        let (sender, receiver) = channel(None);
+        drop(receiver);
+        assert!(sender.send(GuiMessage::Clear).is_err());
+    }
+
+    #[tokio::test]
+    async fn stops_receiving_once_every_sender_is_gone() {
+        let (sender, mut receiver) = channel(None);
+        let clone = sender.clone();
+        drop(sender);
+        clone.send(GuiMessage::Idle(true)).unwrap();
+        clone.send(GuiMessage::Nodes(1, nodes(&["a"]))).unwrap();
+        drop(clone);
+        assert!(matches!(
+            receiver.recv().await,
+            Some(GuiMessage::Idle(true))
+        ));
+        assert!(receiver.recv().await.is_none());
+    }
+}
@@ -0,0 +1,1663 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+mod assembly;
+mod channel;
+mod navigation;
+mod paint;
+mod panels;
+mod transition;
+
+use atspi::{Action, Interface, ObjectRef, Role, State};
+use eframe::egui;
+use egui::{Align2, Color32, FontId, Pos2, Rect};
+use log::{debug, error, info};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::apps::ToolkitInfo;
+use crate::cli::{Args, Coordinates, OverlaySource};
+use crate::collect::Crumb;
+use crate::command::{Command, HitTarget};
+use crate::config::{Calibration, Config, HexColor, HitTestStrategy, MarkerColors, Priority};
+use crate::error::Error;
+use crate::error_log::ErrorLog;
+use crate::export::{write_dot, write_json, write_svg};
+use crate::keys::Hotkey;
+use crate::lint::{Finding, lint, low_contrast, offscreen_focusable};
+use crate::node::{
+    BothExtents, Extents, NodeInfo, address, application_of, changed_nodes, is_wrapper,
+    significant_state, subset, subtree_members,
+};
+use crate::predicate::TextSearch;
+use crate::render::Renderer;
+use crate::session::Frame;
+use crate::stats::NameCoverage;
+use crate::status::StatusLine;
+use assembly::{Assembly, Step};
+pub use channel::{GuiReceiver, GuiSender, channel};
+use navigation::Navigation;
+use paint::{
+    application_color, label_font_size, marker_color, overlay_color, paint_box, paint_flash,
+    paint_focus_conflict, paint_focus_trail, paint_focused_window, paint_hovered, paint_hulls,
+    paint_hyperlinks, paint_image, paint_issues, paint_label, paint_legend, paint_marker,
+    paint_new, paint_offscreen, paint_outline, paint_overlay, paint_pattern, paint_region,
+    paint_search_match, paint_selected, paint_selection, paint_status, paint_summary,
+    paint_tab_stop, paint_table, paint_toast, paint_toolkit, paint_truncated, paint_value,
+};
+use transition::Transition;
+
+/// Messages sent from the collector tasks to the GUI.
+pub enum GuiMessage {
+    /// Nodes of the identified collection, in traversal order, resolved since its
+    /// previous batch.
+    Nodes(u64, Vec<NodeInfo>),
+    /// The identified collection has handed over all of its nodes.
+    TreeFinished(u64),
+    /// Nothing can be collected any more, e.g. because the application quit.
+    Clear,
+    /// The path from the application down to the root of the identified collection.
+    Ancestry(u64, Vec<Crumb>),
+    /// The top-level window containing the focused accessible.
+    FocusedWindow(NodeInfo),
+    /// A node that just received focus.
+    Focused(NodeInfo),
+    /// The name of the application now being shown when cycling applications.
+    Application(String),
+    /// Whether collection is paused because nothing worth showing has focus.
+    Idle(bool),
+    /// The toolkit of the application the next tree is collected from.
+    Toolkit(Option<ToolkitInfo>),
+    /// The screen and window extents of an inspected node.
+    BothExtents(ObjectRef, BothExtents),
+    /// The interfaces implemented by an inspected node.
+    Interfaces(ObjectRef, Vec<Interface>),
+    /// The actions offered by an inspected node.
+    Actions(ObjectRef, Vec<Action>),
+    /// Something worth telling the user, shown as a toast.
+    Notice(String),
+    /// With `--connect`, whether the remote collector is reachable.
+    Connected(bool),
+    /// The accessible AT-SPI reports at `point`, in screen coordinates; `None` if it
+    /// could not tell.
+    HitTest {
+        target: HitTarget,
+        point: Pos2,
+        node: Option<NodeInfo>,
+    },
+}
+
+/// Most nodes failing the contrast check listed with their ratios on the overlay.
+const LOW_CONTRAST_LISTED: usize = 5;
+
+/// Minimum time between two hit tests of the hover inspector.
+const HOVER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the overlay is repainted to show nodes arriving while a tree is collected.
+const COLLECTING_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where `S` saves the tree on screen when `--export-svg` is not given.
+const DEFAULT_SVG_PATH: &str = "atspi-tree.svg";
+
+/// How long a node that changed between two collections stays highlighted.
+const CHANGE_FLASH_DURATION: Duration = Duration::from_secs(1);
+
+/// How long a confirmation such as "Copied" stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+/// Font size of node labels in logical points when the config sets none.
+const DEFAULT_LABEL_SIZE: f32 = 11.0;
+
+/// How often the `--tui-status` line is rewritten, which also keeps its frame rate and
+/// error count current while nothing else redraws.
+const STATUS_LINE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How far back the `--tui-status` line counts errors and warnings.
+const STATUS_ERROR_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long one pulse of the selected and flagged outlines takes, in seconds.
+const PULSE_PERIOD: f64 = 1.2;
+
+/// How many focus changes the focus trail remembers.
+const FOCUS_TRAIL_CAPACITY: usize = 50;
+
+/// Offset nudge in pixels per arrow key press; Shift multiplies it by ten.
+const CALIBRATION_NUDGE: f32 = 1.0;
+/// Scale change per `+`/`-` key press.
+const CALIBRATION_SCALE_STEP: f32 = 0.01;
+
+pub struct ScreenPainterGUI {
+    args: Arc<Args>,
+    config: Config,
+    config_path: PathBuf,
+    messages: GuiReceiver,
+    commands: UnboundedSender<Command>,
+    errors: Arc<ErrorLog>,
+    show_errors: bool,
+    /// When set, nothing is drawn and all input passes through, though collected trees
+    /// are still taken so the overlay is up to date once revealed.
+    hidden: bool,
+    /// Set while collection is paused because nothing worth showing has focus; nothing
+    /// is drawn meanwhile.
+    idle: bool,
+    /// The tree on screen; while a collection is in progress, the nodes it has handed
+    /// over so far.
+    state: Option<Vec<NodeInfo>>,
+    /// How batches of collected nodes fold into `state`.
+    assembly: Assembly,
+    /// The collection the breadcrumb was resolved for, and its path down to the root.
+    ancestry: Option<(u64, Vec<Crumb>)>,
+    /// The nodes on screen when the baseline was marked with `M`; nodes collected since
+    /// that are not among them are highlighted as new.
+    baseline: Option<HashSet<ObjectRef>>,
+    /// Roots changed to by zooming, undone and redone with `Ctrl+Z` and `Ctrl+Shift+Z`.
+    navigation: Navigation,
+    /// The last finished tree, kept while its successor is collected to detect changes.
+    previous: Option<Vec<NodeInfo>>,
+    /// The animation from the previous tree to the one on screen, while it runs.
+    transition: Option<Transition>,
+    focused_window: Option<NodeInfo>,
+    application: Option<String>,
+    /// The monitor the overlay covers, if not the whole screen.
+    monitor: Option<Extents>,
+    /// The overlay's area as of the last frame, which size-only extents are centered in.
+    screen: Rect,
+    /// The terminal line `--tui-status` keeps current, when enabled.
+    status_line: Option<Arc<StatusLine>>,
+    /// When each frame of the last second was drawn, for the status line's frame rate.
+    frame_times: VecDeque<Instant>,
+    /// When the status line was last rewritten.
+    status_updated: Option<Instant>,
+    toolkit: Option<ToolkitInfo>,
+    /// Most recently focused nodes, oldest first.
+    focus_trail: VecDeque<NodeInfo>,
+    /// The most recently focused node and since when, until it has kept focus for
+    /// `--focus-dwell` and joins the trail.
+    pending_focus: Option<(NodeInfo, Instant)>,
+    show_hulls: bool,
+    /// When set, only nodes failing a lint rule are drawn, labelled with their issues.
+    show_lint: bool,
+    /// Lint results for the current tree, computed when lint mode is on.
+    issues: Vec<Finding>,
+    /// Indices of the nodes in `issues`, to filter the tree by without a search per node.
+    flagged: HashSet<usize>,
+    /// When set, each node is labelled with its role and name.
+    show_labels: bool,
+    /// When set, image nodes are outlined by whether they have a text alternative.
+    show_images: bool,
+    /// When set, tab stops are outlined and counted, highlighting the focused one.
+    show_tab_stops: bool,
+    /// When set, nodes that changed since the previous collection briefly flash.
+    show_changes: bool,
+    /// Nodes matching the search from the controls are highlighted.
+    search: TextSearch,
+    /// When set, collected nodes without extents, which cannot be drawn, are listed in
+    /// a panel in interactive mode.
+    show_no_geometry: bool,
+    /// When each recently changed node was detected.
+    flashes: HashMap<ObjectRef, Instant>,
+    /// When set, mouse passthrough is disabled so on-screen controls can be used.
+    interactive: bool,
+    /// Deepest level rendered; `None` renders the whole tree.
+    max_depth: Option<usize>,
+    /// Roles unchecked in the roles panel; nodes with them are not rendered.
+    hidden_roles: HashSet<Role>,
+    /// When set, the node under the pointer is highlighted continuously; like interactive
+    /// mode this disables mouse passthrough, as the pointer is not seen otherwise.
+    hover: bool,
+    /// The smallest node under the pointer in hover mode.
+    hovered: Option<NodeInfo>,
+    /// Pointer position and time of the last hover hit test.
+    last_hover: Option<(Pos2, Instant)>,
+    /// The node shown in the inspector, picked by clicking it in interactive mode.
+    selected: Option<NodeInfo>,
+    /// The node whose subtree alone is drawn at full opacity.
+    solo: Option<ObjectRef>,
+    /// The label and screen position of the window extents are drawn relative to.
+    origin: Option<(String, Pos2)>,
+    /// The action of the selected node awaiting confirmation before it is invoked.
+    pending_action: Option<usize>,
+    /// Screen region collection is limited to, initially `--region`.
+    region: Option<Extents>,
+    /// Where the Shift+drag redrawing the region started.
+    region_drag: Option<Pos2>,
+    /// When the overlay last re-asserted that it stays on top, with `--on-top-interval`.
+    last_on_top: Instant,
+    /// When the overlay closes itself, with `--duration`.
+    deadline: Option<Instant>,
+    /// A short confirmation on screen and when it was shown.
+    toast: Option<(String, Instant)>,
+    /// Whether collected trees are being recorded to the session file.
+    recording: bool,
+    /// With `--connect`, whether the remote collector is connected; trees are only
+    /// received, so nothing answers queries about them.
+    connected: Option<bool>,
+    /// The frames of a replayed session, empty when showing live trees.
+    replay: Vec<Frame>,
+    /// The replayed frame on screen.
+    replay_index: usize,
+    /// Trees loaded with `--overlay`, drawn in their own colors.
+    overlays: Vec<(OverlaySource, Vec<NodeInfo>)>,
+}
+
+impl ScreenPainterGUI {
+    pub fn new(
+        rx_gui: GuiReceiver,
+        commands: UnboundedSender<Command>,
+        args: Arc<Args>,
+        config: Config,
+        config_path: PathBuf,
+        errors: Arc<ErrorLog>,
+        replay: Vec<Frame>,
+    ) -> Self {
+        let mut gui = Self {
+            config,
+            config_path,
+            messages: rx_gui,
+            commands,
+            errors,
+            show_errors: false,
+            hidden: args.hidden,
+            idle: false,
+            state: None,
+            assembly: Assembly::default(),
+            ancestry: None,
+            navigation: Navigation::default(),
+            baseline: None,
+            previous: None,
+            transition: None,
+            focused_window: None,
+            application: None,
+            monitor: None,
+            screen: Rect::NOTHING,
+            status_line: None,
+            frame_times: VecDeque::new(),
+            status_updated: None,
+            toolkit: None,
+            focus_trail: VecDeque::new(),
+            pending_focus: None,
+            show_hulls: false,
+            show_lint: false,
+            issues: Vec::new(),
+            flagged: HashSet::new(),
+            show_labels: false,
+            show_images: false,
+            show_tab_stops: false,
+            show_changes: false,
+            search: TextSearch::default(),
+            show_no_geometry: false,
+            flashes: HashMap::new(),
+            interactive: false,
+            hover: false,
+            hovered: None,
+            last_hover: None,
+            max_depth: None,
+            hidden_roles: HashSet::new(),
+            selected: None,
+            solo: None,
+            pending_action: None,
+            region: args.region,
+            region_drag: None,
+            last_on_top: Instant::now(),
+            deadline: args
+                .duration
+                .map(|seconds| Instant::now() + Duration::from_secs_f64(seconds)),
+            toast: None,
+            origin: None,
+            recording: args.record.is_some(),
+            connected: args.connect.as_ref().map(|_| false),
+            replay,
+            replay_index: 0,
+            overlays: Vec::new(),
+            args,
+        };
+        if !gui.replay.is_empty() {
+            gui.show_frame(0);
+        }
+        gui
+    }
+
+    /// Draws the trees of `overlays` on top of the collected one.
+    pub fn with_overlays(mut self, overlays: Vec<(OverlaySource, Vec<NodeInfo>)>) -> Self {
+        self.overlays = overlays;
+        self
+    }
+
+    /// Draws extents relative to the top-left of `monitor`, which the overlay covers
+    /// with `--monitor`.
+    pub fn with_monitor(mut self, monitor: Option<Extents>) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// Keeps `status` current with `--tui-status`.
+    pub fn with_status_line(mut self, status: Option<Arc<StatusLine>>) -> Self {
+        self.status_line = status;
+        self
+    }
+
+    /// Rewrites the `--tui-status` line at most every `STATUS_LINE_INTERVAL`.
+    fn update_status_line(&mut self, ctx: &egui::Context) {
+        let Some(status) = &self.status_line else {
+            return;
+        };
+        let now = Instant::now();
+        self.frame_times.push_back(now);
+        while self
+            .frame_times
+            .front()
+            .is_some_and(|drawn| now - *drawn > Duration::from_secs(1))
+        {
+            self.frame_times.pop_front();
+        }
+        ctx.request_repaint_after(STATUS_LINE_INTERVAL);
+        if self
+            .status_updated
+            .is_some_and(|updated| updated.elapsed() < STATUS_LINE_INTERVAL)
+        {
+            return;
+        }
+        self.status_updated = Some(now);
+
+        let nodes = self.state.as_ref().map_or(0, Vec::len);
+        let application = self
+            .application
+            .clone()
+            .or_else(|| {
+                let (_, crumbs) = self.ancestry.as_ref()?;
+                Some(crumbs.first()?.label.clone())
+            })
+            .unwrap_or_else(|| "no application".into());
+        let errors = self.errors.count_since(STATUS_ERROR_WINDOW);
+        status.update(format!(
+            "{nodes} nodes | {} fps | {application} | {errors} errors in the last minute",
+            self.frame_times.len()
+        ));
+    }
+
+    /// The root of the tree on screen, as reported by its ancestry.
+    fn root(&self) -> Option<ObjectRef> {
+        let (_, crumbs) = self.ancestry.as_ref()?;
+        Some(crumbs.last()?.object.clone())
+    }
+
+    /// Collects the subtree of `root` instead, remembering the current root for undo.
+    fn navigate(&mut self, root: ObjectRef) {
+        if let Some(current) = self.root() {
+            self.navigation.visit(current);
+        }
+        self.send_command(Command::ZoomTo(root));
+    }
+
+    fn send_command(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            debug!("No collector is listening for commands");
+        }
+    }
+
+    fn handle_input(&mut self, ctx: &egui::Context) {
+        // Keys typed into a text field, e.g. the search, are not hotkeys.
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let pressed = ctx.input_mut(|i| self.config.keys.pressed(i));
+        let pressed = |action| pressed.contains(&action);
+        if pressed(Hotkey::ToggleOverlay) {
+            self.hidden = !self.hidden;
+            self.update_passthrough(ctx);
+        }
+        if self.hidden {
+            return;
+        }
+        if self.args.applications {
+            if pressed(Hotkey::NextApplication) {
+                self.send_command(Command::NextApplication);
+            }
+            if pressed(Hotkey::PreviousApplication) {
+                self.send_command(Command::PreviousApplication);
+            }
+        }
+        if pressed(Hotkey::ClearFocusTrail) {
+            self.focus_trail.clear();
+            self.pending_focus = None;
+        }
+        if pressed(Hotkey::ToggleHulls) {
+            self.show_hulls = !self.show_hulls;
+        }
+        if pressed(Hotkey::ToggleSolo) {
+            self.toggle_solo();
+        }
+        if pressed(Hotkey::LiftSolo) {
+            self.solo = None;
+        }
+        if pressed(Hotkey::RedoRoot) {
+            match self.navigation.forward(self.root()) {
+                Some(root) => self.send_command(Command::ShowRoot(root)),
+                None => self.toast = Some(("No root to redo".into(), Instant::now())),
+            }
+        }
+        if pressed(Hotkey::UndoRoot) {
+            match self.navigation.back(self.root()) {
+                Some(root) => self.send_command(Command::ShowRoot(root)),
+                None => self.toast = Some(("No root to undo".into(), Instant::now())),
+            }
+        }
+        if pressed(Hotkey::Zoom)
+            && let Some(selected) = &self.selected
+        {
+            self.navigate(selected.object.clone());
+        }
+        if pressed(Hotkey::CopyAddress)
+            && let Some(selected) = &self.selected
+        {
+            let address = address(&selected.object);
+            ctx.copy_text(address.clone());
+            self.toast = Some((format!("Copied {address}"), Instant::now()));
+        }
+        if pressed(Hotkey::ToggleOrigin) {
+            self.toggle_origin();
+        }
+        if pressed(Hotkey::ZoomOut) {
+            if let Some(root) = self.root() {
+                self.navigation.visit(root);
+            }
+            self.send_command(Command::ZoomOut);
+        }
+        if pressed(Hotkey::Save) {
+            self.save_exports();
+        }
+        if pressed(Hotkey::Capture) {
+            self.capture();
+        }
+        if pressed(Hotkey::ToggleLint) {
+            self.show_lint = !self.show_lint;
+            self.update_lint();
+        }
+        if pressed(Hotkey::ToggleLabels) {
+            self.show_labels = !self.show_labels;
+        }
+        if pressed(Hotkey::ToggleImages) {
+            self.show_images = !self.show_images;
+        }
+        if self.replay.is_empty() {
+            if pressed(Hotkey::ToggleRecording) {
+                self.recording = !self.recording;
+                self.send_command(Command::SetRecording(self.recording));
+            }
+        } else {
+            if pressed(Hotkey::PreviousFrame) {
+                self.show_frame(self.replay_index.saturating_sub(1));
+            }
+            if pressed(Hotkey::NextFrame) {
+                self.show_frame(self.replay_index + 1);
+            }
+        }
+        if pressed(Hotkey::CycleMarkerColors) {
+            self.config.marker_colors = self.config.marker_colors.next();
+            self.save_config();
+        }
+        if pressed(Hotkey::ToggleTabStops) {
+            self.show_tab_stops = !self.show_tab_stops;
+        }
+        if pressed(Hotkey::ToggleChanges) {
+            self.show_changes = !self.show_changes;
+            self.flashes.clear();
+        }
+        if pressed(Hotkey::ToggleNoGeometry) {
+            self.show_no_geometry = !self.show_no_geometry;
+        }
+        if pressed(Hotkey::ToggleErrors) {
+            self.show_errors = !self.show_errors;
+        }
+        if pressed(Hotkey::ReleaseDocument) && self.args.first_only {
+            self.send_command(Command::ReleaseDocument);
+            self.toast = Some((
+                "Showing the next document that loads".into(),
+                Instant::now(),
+            ));
+        }
+        if pressed(Hotkey::ResetRegion) && self.region != self.monitor {
+            self.set_region(self.monitor);
+        }
+        if pressed(Hotkey::ToggleInteractive) {
+            self.interactive = !self.interactive;
+            self.update_passthrough(ctx);
+        }
+        if pressed(Hotkey::ToggleHover) {
+            self.hover = !self.hover;
+            self.hovered = None;
+            self.last_hover = None;
+            self.update_passthrough(ctx);
+        }
+        if pressed(Hotkey::MarkBaseline) {
+            self.mark_baseline();
+        }
+        if pressed(Hotkey::ClearBaseline) && self.baseline.take().is_some() {
+            self.toast = Some(("Cleared the baseline".into(), Instant::now()));
+        }
+        self.handle_calibration_input(ctx);
+    }
+
+    /// Remembers the nodes on screen, so those appearing later can be told apart.
+    fn mark_baseline(&mut self) {
+        let Some(state) = &self.state else {
+            self.toast = Some(("No tree to mark as the baseline".into(), Instant::now()));
+            return;
+        };
+        let baseline: HashSet<ObjectRef> = state.iter().map(|node| node.object.clone()).collect();
+        let notice = format!("Marked a baseline of {} nodes", baseline.len());
+        self.baseline = Some(baseline);
+        self.toast = Some((notice, Instant::now()));
+    }
+
+    /// Whether the tree on screen comes from a local AT-SPI connection that can be queried,
+    /// rather than from a replay or a remote collector.
+    fn is_live(&self) -> bool {
+        self.replay.is_empty() && self.connected.is_none()
+    }
+
+    /// Mouse passthrough is only disabled while a mode needs the pointer, or while
+    /// showing trees that are not live, e.g. when replaying, as the timeline is always
+    /// usable.
+    fn update_passthrough(&self, ctx: &egui::Context) {
+        let passthrough = self.hidden || !(self.interactive || self.hover || !self.is_live());
+        ctx.send_viewport_cmd(egui::ViewportCommand::MousePassthrough(passthrough));
+    }
+
+    /// Re-runs the hover hit test when the pointer moved, at most every `HOVER_INTERVAL`.
+    fn update_hover(&mut self, ctx: &egui::Context) {
+        let Some(pos) = ctx.pointer_hover_pos() else {
+            self.hovered = None;
+            return;
+        };
+        if let Some((last_pos, last_time)) = self.last_hover
+            && (last_pos == pos || last_time.elapsed() < HOVER_INTERVAL)
+        {
+            if last_pos != pos {
+                ctx.request_repaint_after(HOVER_INTERVAL);
+            }
+            return;
+        }
+        self.last_hover = Some((pos, Instant::now()));
+        self.hit_test(HitTarget::Hover, pos);
+    }
+
+    /// Finds the node at `pos` using the configured strategy. AT-SPI hit tests answer
+    /// asynchronously through `GuiMessage::HitTest`.
+    fn hit_test(&mut self, target: HitTarget, pos: Pos2) {
+        match self.config.hit_test {
+            // Nothing answers hit tests while replaying or receiving.
+            HitTestStrategy::AtPoint if self.is_live() => self.send_command(Command::HitTest {
+                target,
+                point: self.calibration().invert_pos(pos),
+            }),
+            HitTestStrategy::AtPoint | HitTestStrategy::Rects => {
+                let node = self.node_at(pos).cloned();
+                self.set_hit(target, node);
+            }
+        }
+    }
+
+    pub(super) fn set_hit(&mut self, target: HitTarget, node: Option<NodeInfo>) {
+        match target {
+            HitTarget::Select => {
+                self.pending_action = None;
+                if let Some(node) = &node
+                    && node.both_extents.is_none()
+                    && node.extents.is_some()
+                    && self.is_live()
+                {
+                    self.send_command(Command::QueryBothExtents(node.object.clone()));
+                }
+                self.selected = node;
+            }
+            HitTarget::Hover if self.hover => self.hovered = node,
+            HitTarget::Hover => {}
+        }
+    }
+
+    /// Selects nodes on click and redraws the collection region on Shift+drag.
+    fn handle_pointer(&mut self, ctx: &egui::Context, response: &egui::Response) {
+        if response.clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            self.hit_test(HitTarget::Select, pos);
+        }
+        if response.drag_started() && ctx.input(|i| i.modifiers.shift) {
+            self.region_drag = response.interact_pointer_pos();
+        }
+        if response.drag_stopped()
+            && let Some(start) = self.region_drag.take()
+            && let Some(end) = ctx.pointer_interact_pos()
+        {
+            let rect = self.calibration().invert(Rect::from_two_pos(start, end));
+            let region = Extents::from_rect(rect);
+            if region.width > 0 && region.height > 0 {
+                self.set_region(Some(region));
+            }
+        }
+    }
+
+    fn set_region(&mut self, region: Option<Extents>) {
+        self.region = region;
+        self.send_command(Command::SetRegion(region));
+    }
+
+    /// Nudges the calibration with the arrow keys and `+`/`-`, `0` resets it.
+    /// Any change is persisted to the config file immediately.
+    fn handle_calibration_input(&mut self, ctx: &egui::Context) {
+        let before = self.config.calibration;
+        let calibration = &mut self.config.calibration;
+
+        ctx.input(|i| {
+            let step = if i.modifiers.shift {
+                CALIBRATION_NUDGE * 10.0
+            } else {
+                CALIBRATION_NUDGE
+            };
+            if i.key_pressed(egui::Key::ArrowLeft) {
+                calibration.offset_x -= step;
+            }
+            if i.key_pressed(egui::Key::ArrowRight) {
+                calibration.offset_x += step;
+            }
+            if i.key_pressed(egui::Key::ArrowUp) {
+                calibration.offset_y -= step;
+            }
+            if i.key_pressed(egui::Key::ArrowDown) {
+                calibration.offset_y += step;
+            }
+            if i.key_pressed(egui::Key::Plus) || i.key_pressed(egui::Key::Equals) {
+                calibration.scale += CALIBRATION_SCALE_STEP;
+            }
+            if i.key_pressed(egui::Key::Minus) {
+                calibration.scale = (calibration.scale - CALIBRATION_SCALE_STEP).max(0.1);
+            }
+            if i.key_pressed(egui::Key::Num0) {
+                *calibration = Calibration::default();
+            }
+        });
+
+        if self.config.calibration != before {
+            self.save_config();
+        }
+    }
+
+    /// Solos the selected node's subtree, or lifts the solo if it is already soloed.
+    fn toggle_solo(&mut self) {
+        let selected = self.selected.as_ref().map(|node| &node.object);
+        self.solo = match (&self.solo, selected) {
+            (Some(solo), Some(selected)) if solo == selected => None,
+            (_, Some(selected)) => Some(selected.clone()),
+            (solo, None) => solo.clone(),
+        };
+    }
+
+    /// Saves the tree on screen as an SVG, and as JSON and DOT to the paths given for
+    /// them; with `--export-filtered`, only the nodes drawn at full opacity are saved.
+    fn save_exports(&self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let nodes = if self.args.export_filtered {
+            Cow::Owned(subset(state, &self.shown(state)))
+        } else {
+            Cow::Borrowed(state.as_slice())
+        };
+        let anonymized = self.args.anonymize;
+
+        let path = self
+            .args
+            .export_svg
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SVG_PATH.into());
+        match write_svg(&nodes, &path, anonymized) {
+            Ok(()) => info!("Saved SVG to {}", path.display()),
+            Err(err) => error!("Error exporting SVG: {err}"),
+        }
+        if let Some(path) = &self.args.export_json {
+            match write_json(&nodes, self.toolkit.as_ref(), path, anonymized) {
+                Ok(()) => info!("Saved JSON to {}", path.display()),
+                Err(err) => error!("Error exporting JSON: {err}"),
+            }
+        }
+        if let Some(path) = &self.args.export_dot {
+            match write_dot(&nodes, path, anonymized) {
+                Ok(()) => info!("Saved DOT to {}", path.display()),
+                Err(err) => error!("Error exporting DOT: {err}"),
+            }
+        }
+    }
+
+    /// Writes the whole tree on screen as JSON to a new file named after the current time
+    /// in the config's `capture_dir`, confirming the path with a toast.
+    fn capture(&mut self) {
+        let Some(state) = &self.state else {
+            self.toast = Some(("No tree to capture".into(), Instant::now()));
+            return;
+        };
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let dir = self.config.capture_dir.clone().unwrap_or_default();
+        let path = dir.join(format!("atspi-capture-{millis}.json"));
+
+        let written = fs::create_dir_all(&dir)
+            .map_err(|err| Error::render(&dir, err))
+            .and_then(|()| write_json(state, self.toolkit.as_ref(), &path, self.args.anonymize));
+        self.toast = Some(match written {
+            Ok(()) => {
+                info!("Captured tree to {}", path.display());
+                (format!("Captured {}", path.display()), Instant::now())
+            }
+            Err(err) => {
+                error!("Error capturing tree: {err}");
+                ("Capture failed, see the error log".into(), Instant::now())
+            }
+        });
+    }
+
+    /// Marks the nodes of `state` inside the soloed subtree; `None` without a solo.
+    fn solo_members(&self, state: &[NodeInfo]) -> Option<Vec<bool>> {
+        let solo = self.solo.as_ref()?;
+        let root = state.iter().position(|node| &node.object == solo)?;
+        Some(subtree_members(state, root))
+    }
+
+    /// Marks the nodes of `state` drawn at full opacity: within the max depth, failing a
+    /// lint rule in lint mode, and inside the soloed subtree, if any.
+    fn shown(&self, state: &[NodeInfo]) -> Vec<bool> {
+        let solo_members = self.solo_members(state);
+        (0..state.len())
+            .map(|index| {
+                self.is_visible(&state[index])
+                    && (!self.show_lint || self.flagged.contains(&index))
+                    && solo_members.as_ref().is_none_or(|members| members[index])
+            })
+            .collect()
+    }
+
+    fn save_config(&self) {
+        match self.config.save(&self.config_path) {
+            Ok(()) => debug!("Saved config to {}", self.config_path.display()),
+            Err(err) => error!(
+                "Error saving config to {}: {err}",
+                self.config_path.display()
+            ),
+        }
+    }
+
+    /// Takes every pending message.
+    fn drain_messages(&mut self) {
+        while let Some(message) = self.messages.try_recv() {
+            match message {
+                GuiMessage::Nodes(collection, nodes) => self.add_nodes(collection, nodes),
+                GuiMessage::TreeFinished(collection) => self.finish_tree(collection),
+                GuiMessage::Clear => self.clear(),
+                GuiMessage::Ancestry(collection, crumbs) => {
+                    if self
+                        .ancestry
+                        .as_ref()
+                        .is_none_or(|(shown, _)| collection >= *shown)
+                    {
+                        self.ancestry = Some((collection, crumbs));
+                    }
+                }
+                GuiMessage::FocusedWindow(node) => self.focused_window = Some(node),
+                GuiMessage::Focused(node) => self.pending_focus = Some((node, Instant::now())),
+                GuiMessage::Application(name) => self.application = Some(name),
+                GuiMessage::Toolkit(toolkit) => self.toolkit = toolkit,
+                GuiMessage::Idle(idle) => self.idle = idle,
+                GuiMessage::Notice(notice) => self.toast = Some((notice, Instant::now())),
+                GuiMessage::Connected(connected) => self.connected = Some(connected),
+                GuiMessage::BothExtents(object, extents) => {
+                    self.update_node(&object, |node| node.both_extents = Some(extents))
+                }
+                GuiMessage::Interfaces(object, interfaces) => {
+                    self.update_node(&object, |node| node.interfaces = Some(interfaces.clone()))
+                }
+                GuiMessage::Actions(object, actions) => {
+                    self.update_node(&object, |node| node.actions = Some(actions.clone()))
+                }
+                GuiMessage::HitTest {
+                    target,
+                    point,
+                    node,
+                } => {
+                    let node = node.or_else(|| {
+                        let pos = self.calibration().apply_pos(point);
+                        self.node_at(pos).cloned()
+                    });
+                    self.set_hit(target, node);
+                }
+            }
+        }
+    }
+
+    /// Adds a batch of `nodes` to the tree on screen.
+    ///
+    /// The first batch of a newer collection replaces the previous tree, while batches
+    /// of collections started before the newest one are outdated and dropped.
+    fn add_nodes(&mut self, collection: u64, nodes: Vec<NodeInfo>) {
+        match self.assembly.batch(collection) {
+            Step::Extend => self.state.get_or_insert_default().extend(nodes),
+            Step::Replace => {
+                // Indices into the previous tree no longer apply.
+                self.set_issues(Vec::new());
+                self.previous = self.state.replace(nodes);
+                self.transition = self
+                    .previous
+                    .as_deref()
+                    .and_then(|previous| self.start_transition(previous));
+            }
+            _ => {}
+        }
+    }
+
+    /// Completes the tree of `collection` once all of its nodes have arrived.
+    ///
+    /// A newer collection finishing without having handed over any nodes found an empty
+    /// tree, which replaces the one on screen.
+    fn finish_tree(&mut self, collection: u64) {
+        match self.assembly.finished(collection) {
+            Step::Complete => {
+                if let Some(previous) = self.previous.take() {
+                    if let (Some(transition), Some(state)) = (&mut self.transition, &self.state) {
+                        transition.finish(&previous, state);
+                    }
+                    self.flash_changes(&previous);
+                }
+                self.update_lint();
+            }
+            Step::Empty => self.clear(),
+            _ => {}
+        }
+    }
+
+    /// Removes the tree and everything derived from it from the overlay, e.g. because
+    /// the application it was collected from went away.
+    fn clear(&mut self) {
+        self.state = None;
+        self.previous = None;
+        self.transition = None;
+        self.ancestry = None;
+        self.toolkit = None;
+        self.set_issues(Vec::new());
+        self.flashes.clear();
+        self.selected = None;
+        self.pending_action = None;
+        self.hovered = None;
+        self.solo = None;
+        self.origin = None;
+    }
+
+    /// The calibration extents are drawn with, shifted so the chosen origin window, if
+    /// any, or else the `--monitor` covered starts at the top-left corner of the overlay.
+    /// Hit tests invert it, so they still map the pointer back to screen coordinates.
+    fn calibration(&self) -> Calibration {
+        match (&self.origin, self.monitor) {
+            (Some((_, origin)), _) => self.config.calibration.relative_to(*origin),
+            (None, Some(monitor)) => self.config.calibration.relative_to(monitor.rect().min),
+            (None, None) => self.config.calibration,
+        }
+    }
+
+    /// Draws extents relative to the window of the selected node, or back relative to
+    /// the screen if they already are relative to a window.
+    fn toggle_origin(&mut self) {
+        if self.origin.take().is_some() {
+            self.toast = Some(("Extents relative to the screen".into(), Instant::now()));
+            return;
+        }
+        if self.args.coordinates == Some(Coordinates::Window) {
+            self.toast = Some((
+                "Extents are already relative to their window".into(),
+                Instant::now(),
+            ));
+            return;
+        }
+        let Some(window) = self.selected.as_ref().and_then(|node| self.window_of(node)) else {
+            self.toast = Some(("Select a node in a window first".into(), Instant::now()));
+            return;
+        };
+        let extents = window.extents.unwrap();
+        let (label, origin) = (
+            window.label(),
+            Pos2::new(extents.x as f32, extents.y as f32),
+        );
+        self.toast = Some((format!("Extents relative to {label}"), Instant::now()));
+        self.origin = Some((label, origin));
+    }
+
+    /// The outermost ancestor-or-self of `node` with extents, which is its top-level
+    /// window as applications report none themselves.
+    fn window_of<'a>(&'a self, node: &'a NodeInfo) -> Option<&'a NodeInfo> {
+        let state = self.state.as_deref()?;
+        let Some(mut index) = state.iter().position(|other| other.object == node.object) else {
+            return node.extents.is_some().then_some(node);
+        };
+        let mut window = None;
+        loop {
+            if state[index].extents.is_some() {
+                window = Some(&state[index]);
+            }
+            match state[index].parent {
+                Some(parent) => index = parent,
+                None => return window,
+            }
+        }
+    }
+
+    /// Adds the pending focused node to the trail once it has kept focus for
+    /// `--focus-dwell`, and otherwise checks back when it will have.
+    fn settle_focus(&mut self, ctx: &egui::Context) {
+        let Some((_, focused)) = &self.pending_focus else {
+            return;
+        };
+        let dwell = Duration::from_secs_f64(self.args.focus_dwell);
+        if focused.elapsed() < dwell {
+            ctx.request_repaint_after(dwell - focused.elapsed());
+            return;
+        }
+        let (node, _) = self.pending_focus.take().unwrap();
+        // Focus returning to the last node in the trail after a bounce is not a new step.
+        if self
+            .focus_trail
+            .back()
+            .is_some_and(|last| last.object == node.object)
+        {
+            return;
+        }
+        if self.focus_trail.len() == FOCUS_TRAIL_CAPACITY {
+            self.focus_trail.pop_front();
+        }
+        self.focus_trail.push_back(node);
+    }
+
+    /// How far the selected and flagged outlines are into their pulse, from 0 to 1 and
+    /// back within `PULSE_PERIOD`; always 0 with `reduce_motion`. Derived from egui's
+    /// clock, so the pulse runs at the same speed at any frame rate.
+    fn pulse(&self, ctx: &egui::Context) -> f32 {
+        if self.config.reduce_motion {
+            return 0.0;
+        }
+        let phase = ctx.input(|i| i.time) / PULSE_PERIOD * std::f64::consts::TAU;
+        (0.5 - 0.5 * phase.cos()) as f32
+    }
+
+    /// The name of the application the tree on screen comes from, as far as it is known.
+    fn application_name(&self) -> &str {
+        self.application
+            .as_deref()
+            .or(self
+                .toolkit
+                .as_ref()
+                .map(|toolkit| toolkit.application.as_str()))
+            .unwrap_or("unknown application")
+    }
+
+    /// Shows the replayed frame at `index`, clamped to the last one.
+    fn show_frame(&mut self, index: usize) {
+        let index = index.min(self.replay.len() - 1);
+        let frame = &self.replay[index];
+        self.replay_index = index;
+        self.toolkit = frame.toolkit.clone();
+        if let Some(previous) = self.state.replace(frame.nodes.clone()) {
+            self.transition = self.start_transition(&previous);
+            if let (Some(transition), Some(state)) = (&mut self.transition, &self.state) {
+                transition.finish(&previous, state);
+            }
+            self.flash_changes(&previous);
+        }
+        self.update_lint();
+    }
+
+    /// The animation away from `previous` to a new tree, unless animations are off.
+    fn start_transition(&self, previous: &[NodeInfo]) -> Option<Transition> {
+        let duration = self.config.transition_duration;
+        (duration > 0.0 && !self.config.reduce_motion)
+            .then(|| Transition::new(previous, Duration::from_secs_f32(duration)))
+    }
+
+    /// Flashes the nodes of the tree on screen that changed since `previous`.
+    fn flash_changes(&mut self, previous: &[NodeInfo]) {
+        if !self.show_changes {
+            return;
+        }
+        if let Some(nodes) = &self.state {
+            let now = Instant::now();
+            for index in changed_nodes(previous, nodes) {
+                self.flashes.insert(nodes[index].object.clone(), now);
+            }
+        }
+    }
+
+    fn update_lint(&mut self) {
+        let issues = match (&self.state, self.show_lint) {
+            (Some(state), true) => lint(state, &self.config.lint),
+            _ => Vec::new(),
+        };
+        self.set_issues(issues);
+    }
+
+    fn set_issues(&mut self, issues: Vec<Finding>) {
+        self.flagged = issues.iter().map(|finding| finding.index).collect();
+        self.issues = issues;
+    }
+
+    /// Applies `update` to the inspected node and its entry in the tree, so details
+    /// fetched on demand are included in later JSON copies.
+    fn update_node(&mut self, object: &ObjectRef, update: impl Fn(&mut NodeInfo)) {
+        let tree = self.state.iter_mut().flatten();
+        for node in tree.chain(&mut self.selected) {
+            if &node.object == object {
+                update(node);
+            }
+        }
+    }
+
+    /// Where the box of `extents` is drawn, following the config for partial extents.
+    fn place(&self, extents: &Extents, calibration: &Calibration) -> Option<Rect> {
+        self.config
+            .partial_extents
+            .place(extents, calibration, self.screen)
+    }
+
+    /// Cuts the node indices in `kept` down to `limit` by `priority`, keeping collection
+    /// order among the rest, and returns how many were dropped. The focused and selected
+    /// nodes come first whatever the priority.
+    fn limit_nodes(
+        &self,
+        kept: &mut Vec<usize>,
+        limit: Option<usize>,
+        priority: Priority,
+        state: &[NodeInfo],
+        rects: &[Option<Rect>],
+    ) -> usize {
+        let Some(limit) = limit else {
+            return 0;
+        };
+        if kept.len() <= limit {
+            return 0;
+        }
+        let selected = self.selected.as_ref().map(|node| &node.object);
+        let mut ranked = kept.clone();
+        ranked.sort_by_cached_key(|&index| {
+            let node = &state[index];
+            let pinned = node.states.contains(State::Focused) || selected == Some(&node.object);
+            let rank = match priority {
+                Priority::Largest => {
+                    let area = rects[index].map_or(0.0, |rect| rect.area());
+                    -(area as i64)
+                }
+                Priority::Shallowest => node.depth as i64,
+                Priority::Collection => index as i64,
+            };
+            (!pinned, rank)
+        });
+        ranked.truncate(limit);
+        let omitted = kept.len() - ranked.len();
+        ranked.sort_unstable();
+        *kept = ranked;
+        omitted
+    }
+
+    /// Returns the smallest visible node whose rect contains `pos`.
+    fn node_at(&self, pos: Pos2) -> Option<&NodeInfo> {
+        let calibration = self.calibration();
+        self.state
+            .iter()
+            .flatten()
+            .filter(|node| self.is_visible(node))
+            .filter_map(|node| Some((node, self.place(&node.extents?, &calibration)?)))
+            .filter(|(_, rect)| rect.contains(pos))
+            .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+            .map(|(node, _)| node)
+    }
+
+    /// Whether `node` passes the interactive filters.
+    fn is_visible(&self, node: &NodeInfo) -> bool {
+        self.max_depth
+            .is_none_or(|max_depth| node.depth <= max_depth)
+            && !self.hidden_roles.contains(&node.role)
+    }
+}
+
+impl eframe::App for ScreenPainterGUI {
+    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
+        match self.args.background.or(self.config.background) {
+            Some(HexColor(color)) if !self.hidden => egui::Rgba::from(color).to_array(),
+            _ => egui::Rgba::TRANSPARENT.to_array(),
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                // Closing the window returns from `run_native`, which ends the collection
+                // tasks along with the runtime.
+                info!(
+                    "Stopping after {} seconds",
+                    self.args.duration.unwrap_or_default()
+                );
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                self.deadline = None;
+            } else {
+                ctx.request_repaint_after(remaining);
+            }
+        }
+        if let Some(interval) = self.args.on_top_interval {
+            let interval = Duration::from_secs_f64(interval);
+            if self.last_on_top.elapsed() >= interval {
+                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                    egui::WindowLevel::AlwaysOnTop,
+                ));
+                self.last_on_top = Instant::now();
+            }
+            ctx.request_repaint_after(interval.saturating_sub(self.last_on_top.elapsed()));
+        }
+
+        self.handle_input(ctx);
+
+        self.drain_messages();
+        self.update_status_line(ctx);
+        self.screen = ctx.screen_rect();
+
+        if self.hidden || self.idle {
+            return;
+        }
+        if self.interactive {
+            self.show_controls(ctx);
+            self.show_breadcrumb(ctx);
+            self.show_roles_panel(ctx);
+            if self.show_no_geometry {
+                self.show_no_geometry_panel(ctx);
+            }
+        }
+        if !self.replay.is_empty() {
+            self.show_timeline(ctx);
+        }
+        if self.show_errors {
+            self.show_error_panel(ctx);
+        }
+        if self.selected.is_some() {
+            self.show_inspector(ctx);
+        }
+        if self.hover {
+            self.update_hover(ctx);
+        }
+        self.settle_focus(ctx);
+        if !self.flashes.is_empty() {
+            self.flashes
+                .retain(|_, flashed| flashed.elapsed() < CHANGE_FLASH_DURATION);
+            ctx.request_repaint();
+        }
+        if let Some((_, shown)) = &self.toast {
+            if shown.elapsed() < TOAST_DURATION {
+                ctx.request_repaint_after(TOAST_DURATION - shown.elapsed());
+            } else {
+                self.toast = None;
+            }
+        }
+        if let Some(transition) = &self.transition {
+            if transition.is_done() {
+                self.transition = None;
+            } else {
+                ctx.request_repaint();
+            }
+        }
+        if self.assembly.running() {
+            ctx.request_repaint_after(COLLECTING_REPAINT_INTERVAL);
+        }
+        if !self.config.reduce_motion && (self.selected.is_some() || !self.issues.is_empty()) {
+            ctx.request_repaint();
+        }
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                if self.interactive {
+                    let response = ui.interact(
+                        ui.max_rect(),
+                        ui.id().with("overlay"),
+                        egui::Sense::click_and_drag(),
+                    );
+                    self.handle_pointer(ctx, &response);
+                }
+                let mut egui_painter = ui.painter().clone();
+                let painter: &mut dyn Renderer = &mut egui_painter;
+                let rounding = self.config.corner_radius;
+                let pulse = self.pulse(ctx);
+
+                if let Some(start) = self.region_drag
+                    && let Some(end) = ctx.pointer_interact_pos()
+                {
+                    paint_region(painter, Rect::from_two_pos(start, end));
+                } else if let Some(region) = self.region {
+                    paint_region(painter, self.calibration().apply(region.rect()));
+                }
+
+                if let Some(application) = &self.application {
+                    painter.text(
+                        ui.max_rect().left_top() + egui::vec2(8.0, 8.0),
+                        Align2::LEFT_TOP,
+                        application,
+                        FontId::proportional(20.0),
+                        Color32::WHITE,
+                        f32::INFINITY,
+                    );
+                }
+
+                if let Some(toolkit) = &self.toolkit {
+                    paint_toolkit(painter, ui.max_rect(), toolkit);
+                }
+
+                if let Some((toast, _)) = &self.toast {
+                    paint_toast(painter, ui.max_rect(), toast);
+                }
+
+                if self.recording {
+                    paint_status(painter, ui.max_rect(), "● REC", Color32::RED);
+                } else if self.connected == Some(false) {
+                    let status = format!(
+                        "Reconnecting to {}…",
+                        self.args.connect.as_deref().unwrap_or_default()
+                    );
+                    paint_status(
+                        painter,
+                        ui.max_rect(),
+                        &status,
+                        Color32::from_rgb(230, 150, 0),
+                    );
+                } else if !self.replay.is_empty() {
+                    let status = format!("Frame {} / {}", self.replay_index + 1, self.replay.len());
+                    paint_status(painter, ui.max_rect(), &status, Color32::WHITE);
+                }
+
+                if let Some(window) = &self.focused_window {
+                    paint_focused_window(painter, window, &self.calibration(), rounding);
+                }
+
+                paint_focus_trail(painter, &self.focus_trail, &self.calibration());
+
+                let mut legend = Vec::new();
+                if !self.overlays.is_empty() {
+                    for (index, (source, nodes)) in self.overlays.iter().enumerate() {
+                        let color = overlay_color(index);
+                        let (dx, dy) = source.offset;
+                        let calibration = self
+                            .calibration()
+                            .relative_to(Pos2::new(-dx as f32, -dy as f32));
+                        paint_overlay(painter, nodes, &calibration, color, rounding);
+                        legend.push((source.path.display().to_string(), color));
+                    }
+                }
+
+                if let Some(state) = &self.state {
+                    let calibration = self.calibration();
+                    let place = |extents: Option<Extents>| {
+                        extents.and_then(|extents| self.place(&extents, &calibration))
+                    };
+                    let mut rects: Vec<Option<Rect>> =
+                        state.iter().map(|node| place(node.extents)).collect();
+                    // While animating, nodes of the previous tree glide from where they
+                    // were, and new ones fade in.
+                    let progress = self.transition.as_ref().map(Transition::progress);
+                    let mut fading_in = vec![false; state.len()];
+                    if let (Some(transition), Some(progress)) = (&self.transition, progress) {
+                        for (index, node) in state.iter().enumerate() {
+                            match transition.origin(node).map(place) {
+                                Some(Some(from)) => {
+                                    if let Some(to) = rects[index] {
+                                        rects[index] = Some(from.lerp_towards(&to, progress));
+                                    }
+                                }
+                                Some(None) => {}
+                                None => fading_in[index] = true,
+                            }
+                        }
+                    }
+                    let mut visible: Vec<usize> = (0..state.len())
+                        .filter(|&index| self.is_visible(&state[index]))
+                        .collect();
+                    if self.show_lint {
+                        visible.retain(|index| self.flagged.contains(index));
+                    } else {
+                        // Level of detail: boxes too small to make out at the current
+                        // scale are left out, and reappear as the scale grows. Lint mode
+                        // keeps these and wrappers, as they are often what it flags.
+                        let min_size = self.config.min_box_size;
+                        if min_size > 0.0 {
+                            visible.retain(|&index| {
+                                rects[index]
+                                    .is_none_or(|rect| rect.width().min(rect.height()) >= min_size)
+                            });
+                        }
+                        if let Some(tolerance) = self.config.collapse_wrappers {
+                            visible.retain(|&index| !is_wrapper(state, index, tolerance));
+                        }
+                    }
+
+                    // A buggy application may report focus on several nodes at once.
+                    let focused_nodes = state
+                        .iter()
+                        .filter(|node| node.states.contains(State::Focused))
+                        .count();
+                    // Nodes outside any application node are taken to belong to the one
+                    // shown.
+                    let fallback = self.application_name();
+                    let applications: Vec<&str> =
+                        if self.config.marker_colors == MarkerColors::Application {
+                            (0..state.len())
+                                .map(|index| application_of(state, index).unwrap_or(fallback))
+                                .collect()
+                        } else {
+                            Vec::new()
+                        };
+                    let application =
+                        |index: usize| applications.get(index).copied().unwrap_or(fallback);
+                    let offscreen: Vec<usize> = match self.config.lint.screen {
+                        Some(screen) => (0..state.len())
+                            .filter(|&index| offscreen_focusable(&state[index], &screen).is_some())
+                            .collect(),
+                        None => Vec::new(),
+                    };
+
+                    let over_budget = self.limit_nodes(
+                        &mut visible,
+                        self.config.render_budget,
+                        self.config.render_priority,
+                        state,
+                        &rects,
+                    );
+
+                    // Outside a soloed subtree only heavily dimmed markers are drawn.
+                    let solo_members = self.solo_members(state);
+                    let dimmed =
+                        |index: usize| solo_members.as_ref().is_some_and(|members| !members[index]);
+                    let (dimmed_nodes, visible): (Vec<usize>, Vec<usize>) =
+                        visible.into_iter().partition(|&index| dimmed(index));
+
+                    for &index in &dimmed_nodes {
+                        if let Some(rect) = rects[index] {
+                            paint_marker(painter, rect, Color32::RED.gamma_multiply(0.15));
+                        }
+                    }
+
+                    // Hulls go first so individual node boxes are drawn on top of them.
+                    if self.show_hulls {
+                        paint_hulls(painter, state, &rects, &visible);
+                    }
+
+                    for &index in &visible {
+                        let Some(rect) = rects[index] else {
+                            continue;
+                        };
+                        if fading_in[index]
+                            && let Some(progress) = progress
+                        {
+                            painter.set_opacity(progress);
+                        }
+
+                        if let Some(flashed) = self.flashes.get(&state[index].object) {
+                            let age = flashed.elapsed().as_secs_f32();
+                            let fade = 1.0 - age / CHANGE_FLASH_DURATION.as_secs_f32();
+                            paint_flash(painter, rect, fade, rounding);
+                        }
+
+                        if self.show_images && state[index].is_image() {
+                            paint_image(painter, rect, &state[index], rounding);
+                        }
+
+                        if focused_nodes > 1 && state[index].states.contains(State::Focused) {
+                            paint_focus_conflict(painter, rect, rounding);
+                        }
+
+                        if self.show_tab_stops && state[index].is_tab_stop() {
+                            let focused = state[index].states.contains(State::Focused);
+                            paint_tab_stop(painter, rect, focused, rounding);
+                        }
+
+                        if self
+                            .baseline
+                            .as_ref()
+                            .is_some_and(|baseline| !baseline.contains(&state[index].object))
+                        {
+                            paint_new(painter, rect, rounding);
+                        }
+
+                        if self.search.matches(&state[index]) {
+                            paint_search_match(painter, rect, rounding);
+                        }
+
+                        if let Some(table) = &state[index].table {
+                            paint_table(painter, rect, table, &calibration, rounding);
+                        }
+
+                        if let (Some(children), Some(max_children)) = (
+                            state[index].truncated_children,
+                            self.config.traversal.max_children,
+                        ) {
+                            paint_truncated(painter, rect, children, max_children);
+                        }
+
+                        if let Some(selection) = &state[index].selection {
+                            paint_selection(painter, selection, &calibration, rounding);
+                        }
+
+                        if let Some(links) = &state[index].hyperlinks {
+                            paint_hyperlinks(painter, links, &calibration);
+                        }
+
+                        if self.args.show_values
+                            && let Some(value) = &state[index].value
+                        {
+                            paint_value(painter, rect, value, rounding);
+                        }
+
+                        let color = marker_color(
+                            &state[index],
+                            application(index),
+                            self.config.marker_colors,
+                        );
+                        let outlined = match significant_state(state[index].states) {
+                            Some(significant) if self.config.patterns.enabled => {
+                                let pattern = self.config.patterns.get(significant);
+                                paint_pattern(painter, rect, color, pattern, rounding);
+                                true
+                            }
+                            Some(_) if self.config.marker_colors == MarkerColors::State => {
+                                paint_outline(painter, rect, color, rounding);
+                                true
+                            }
+                            _ => false,
+                        };
+                        let style = self.args.style.unwrap_or(self.config.box_style);
+                        paint_box(painter, rect, color, style, outlined, rounding);
+                        paint_marker(painter, rect, color);
+                        painter.set_opacity(1.0);
+                    }
+
+                    if let (Some(transition), Some(progress)) = (&self.transition, progress) {
+                        painter.set_opacity(1.0 - progress);
+                        for node in &transition.removed {
+                            if let Some(rect) = place(node.extents) {
+                                let color = marker_color(node, fallback, self.config.marker_colors);
+                                paint_outline(painter, rect, color, rounding);
+                                paint_marker(painter, rect, color);
+                            }
+                        }
+                        painter.set_opacity(1.0);
+                    }
+
+                    if self.config.marker_colors == MarkerColors::Application {
+                        let mut listed = HashSet::new();
+                        for &index in &visible {
+                            if listed.insert(application(index)) {
+                                let name = application(index);
+                                legend.push((name.to_string(), application_color(name)));
+                            }
+                        }
+                    }
+
+                    for &index in &visible {
+                        if offscreen.binary_search(&index).is_ok()
+                            && let Some(rect) = rects[index]
+                        {
+                            paint_offscreen(painter, ui.max_rect(), rect, &state[index].label());
+                        }
+                    }
+
+                    let mut labelled: Vec<usize> = Vec::new();
+                    if self.show_labels {
+                        labelled = visible
+                            .iter()
+                            .copied()
+                            .filter(|&index| rects[index].is_some())
+                            .collect();
+                    }
+                    let omitted_labels = self.limit_nodes(
+                        &mut labelled,
+                        self.config.max_labels,
+                        self.config.label_priority,
+                        state,
+                        &rects,
+                    );
+
+                    let mut summary = Vec::new();
+                    if let Some((window, origin)) = &self.origin {
+                        let text = format!(
+                            "origin {window} at ({}, {})",
+                            origin.x as i32, origin.y as i32
+                        );
+                        summary.push((text, Color32::from_rgb(0, 200, 255)));
+                    }
+                    if self.show_lint {
+                        let coverage = NameCoverage::of(state);
+                        let below = self
+                            .args
+                            .min_name_coverage
+                            .is_some_and(|min_percent| coverage.is_below(min_percent));
+                        let color = if below {
+                            Color32::from_rgb(255, 60, 60)
+                        } else {
+                            Color32::WHITE
+                        };
+                        summary.push((coverage.to_string(), color));
+                    }
+                    if self.show_tab_stops {
+                        let count = visible
+                            .iter()
+                            .filter(|&&index| state[index].is_tab_stop())
+                            .count();
+                        let text = match count {
+                            1 => "1 tab stop".to_string(),
+                            count => format!("{count} tab stops"),
+                        };
+                        summary.push((text, Color32::WHITE));
+                    }
+                    if self.config.traversal.contrast {
+                        let min_ratio = self.config.lint.min_contrast;
+                        let failing: Vec<&NodeInfo> = state
+                            .iter()
+                            .filter(|node| low_contrast(node, min_ratio).is_some())
+                            .collect();
+                        let color = Color32::from_rgb(255, 60, 60);
+                        for node in failing.iter().take(LOW_CONTRAST_LISTED) {
+                            let ratio = node.contrast.map_or(0.0, |contrast| contrast.ratio);
+                            let text = format!("contrast {ratio:.2}:1: {}", node.label());
+                            summary.push((text, color));
+                        }
+                        if failing.len() > LOW_CONTRAST_LISTED {
+                            let more = failing.len() - LOW_CONTRAST_LISTED;
+                            summary
+                                .push((format!("{more} more below {min_ratio}:1 contrast"), color));
+                        }
+                    }
+                    if let Some(baseline) = &self.baseline {
+                        let new = state
+                            .iter()
+                            .filter(|node| !baseline.contains(&node.object))
+                            .count();
+                        summary.push((
+                            format!("{new} new since the baseline"),
+                            Color32::from_rgb(0, 230, 170),
+                        ));
+                    }
+                    if focused_nodes > 1 {
+                        let text = format!("{focused_nodes} nodes claim focus");
+                        summary.push((text, Color32::from_rgb(255, 0, 200)));
+                    }
+                    if !offscreen.is_empty() {
+                        let text = match offscreen.len() {
+                            1 => "1 focusable node off screen".to_string(),
+                            count => format!("{count} focusable nodes off screen"),
+                        };
+                        summary.push((text, Color32::from_rgb(230, 150, 0)));
+                    }
+                    if over_budget > 0 {
+                        let text = match over_budget {
+                            1 => "1 node over the render budget not drawn".to_string(),
+                            count => format!("{count} nodes over the render budget not drawn"),
+                        };
+                        summary.push((text, Color32::from_rgb(230, 150, 0)));
+                    }
+                    if omitted_labels > 0 {
+                        let text = match omitted_labels {
+                            1 => "1 label omitted".to_string(),
+                            count => format!("{count} labels omitted"),
+                        };
+                        summary.push((text, Color32::WHITE));
+                    }
+                    paint_summary(painter, ui.max_rect(), &summary);
+
+                    // Labels go on top of every box so neighbors do not cover them.
+                    if self.show_labels {
+                        let font_size = label_font_size(
+                            self.config.label_size.unwrap_or(DEFAULT_LABEL_SIZE),
+                            ctx.native_pixels_per_point().unwrap_or(1.0),
+                            ctx.pixels_per_point(),
+                        );
+                        for &index in &labelled {
+                            if let Some(rect) = rects[index] {
+                                paint_label(
+                                    painter,
+                                    rect,
+                                    &state[index],
+                                    self.config.label_placement,
+                                    font_size,
+                                );
+                            }
+                        }
+                    }
+
+                    for finding in &self.issues {
+                        if let Some(rect) = rects[finding.index]
+                            && self.is_visible(&state[finding.index])
+                            && !dimmed(finding.index)
+                        {
+                            paint_issues(painter, rect, finding, rounding, pulse);
+                        }
+                    }
+                }
+                if !legend.is_empty() {
+                    paint_legend(painter, ui.max_rect(), &legend);
+                }
+
+                if let Some(selected) = &self.selected {
+                    paint_selected(painter, selected, &self.calibration(), rounding, pulse);
+                }
+                if let Some(hovered) = &self.hovered {
+                    paint_hovered(painter, hovered, &self.calibration(), rounding);
+                }
+            });
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::ObjectRef;
+use std::collections::VecDeque;
+
+/// Most roots remembered in each direction; the oldest are forgotten first.
+const HISTORY_LIMIT: usize = 50;
+
+/// The collection roots visited by zooming and through the breadcrumb, stepped through
+/// like a browser's history.
+#[derive(Debug, Default)]
+pub struct Navigation {
+    /// Roots left, most recent last.
+    back: VecDeque<ObjectRef>,
+    /// Roots stepped back from, most recent last; cleared by visiting a new root.
+    forward: Vec<ObjectRef>,
+}
+
+impl Navigation {
+    /// Records leaving `current` for a root that was not stepped to through the history.
+    pub fn visit(&mut self, current: ObjectRef) {
+        if self.back.back() != Some(&current) {
+            if self.back.len() == HISTORY_LIMIT {
+                self.back.pop_front();
+            }
+            self.back.push_back(current);
+        }
+        self.forward.clear();
+    }
+
+    /// The root before `current`, remembering `current` to step forward to again.
+    pub fn back(&mut self, current: Option<ObjectRef>) -> Option<ObjectRef> {
+        let previous = self.back.pop_back()?;
+        if let Some(current) = current {
+            self.forward.push(current);
+            if self.forward.len() > HISTORY_LIMIT {
+                self.forward.remove(0);
+            }
+        }
+        Some(previous)
+    }
+
+    /// The root last stepped back from, remembering `current` to step back to again.
+    pub fn forward(&mut self, current: Option<ObjectRef>) -> Option<ObjectRef> {
+        let next = self.forward.pop()?;
+        if let Some(current) = current {
+            if self.back.len() == HISTORY_LIMIT {
+                self.back.pop_front();
+            }
+            self.back.push_back(current);
+        }
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::names::UniqueName;
+    use zbus::zvariant::ObjectPath;
+
+    fn root(id: usize) -> ObjectRef {
+        let path = format!("/org/a11y/atspi/accessible/{id}");
+        ObjectRef::new(
+            UniqueName::from_static_str_unchecked(":1.1"),
+            ObjectPath::try_from(path).unwrap(),
+        )
+    }
+
+    #[test]
+    fn steps_back_and_forward() {
+        let mut navigation = Navigation::default();
+        navigation.visit(root(0));
+        navigation.visit(root(1));
+        assert_eq!(navigation.back(Some(root(2))), Some(root(1)));
+        assert_eq!(navigation.back(Some(root(1))), Some(root(0)));
+        assert_eq!(navigation.back(Some(root(0))), None);
+        assert_eq!(navigation.forward(Some(root(0))), Some(root(1)));
+        assert_eq!(navigation.forward(Some(root(1))), Some(root(2)));
+        assert_eq!(navigation.forward(Some(root(2))), None);
+        assert_eq!(navigation.back(Some(root(2))), Some(root(1)));
+    }
+
+    #[test]
+    fn visiting_forgets_the_way_forward() {
+        let mut navigation = Navigation::default();
+        navigation.visit(root(0));
+        assert_eq!(navigation.back(Some(root(1))), Some(root(0)));
+        navigation.visit(root(0));
+        assert_eq!(navigation.forward(Some(root(2))), None);
+        assert_eq!(navigation.back(Some(root(2))), Some(root(0)));
+    }
+
+    #[test]
+    fn does_not_repeat_the_last_root() {
+        let mut navigation = Navigation::default();
+        navigation.visit(root(0));
+        navigation.visit(root(0));
+        assert_eq!(navigation.back(None), Some(root(0)));
+        assert_eq!(navigation.back(None), None);
+    }
+
+    #[test]
+    fn forgets_the_oldest_roots_past_the_limit() {
+        let mut navigation = Navigation::default();
+        for id in 0..=HISTORY_LIMIT {
+            navigation.visit(root(id));
+        }
+        let mut steps = Vec::new();
+        while let Some(previous) = navigation.back(None) {
+            steps.push(previous);
+        }
+        assert_eq!(steps.len(), HISTORY_LIMIT);
+        assert_eq!(steps.first(), Some(&root(HISTORY_LIMIT)));
+        assert_eq!(steps.last(), Some(&root(1)));
+    }
+}
@@ -0,0 +1,756 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use egui::ecolor::Hsva;
+use egui::{Align, Align2, Color32, FontId, Pos2, Rangef, Rect, Stroke, StrokeKind, Vec2};
+use std::collections::BTreeMap;
+
+use crate::apps::ToolkitInfo;
+use crate::config::{BoxStyle, Calibration, LabelPlacement, MarkerColors, StatePattern};
+use crate::geometry::convex_hull;
+use crate::lint::Finding;
+use crate::node::{
+    Hyperlink, NodeInfo, NodeValue, SelectionInfo, SignificantState, TableInfo, application_hue,
+    group_hue, significant_state, top_level_ancestor,
+};
+use crate::render::{LinePattern, Renderer};
+
+/// Picks a distinct translucent color for the group at `index`.
+pub fn group_color(index: usize) -> Color32 {
+    Hsva::new(group_hue(index), 0.8, 0.9, 0.25).into()
+}
+
+/// Draws a translucent convex hull around all descendants of each top-level container.
+///
+/// Only the nodes at the `visible` indices contribute to the hulls; `rects` holds each
+/// node's on-screen rect.
+pub fn paint_hulls(
+    painter: &mut dyn Renderer,
+    nodes: &[NodeInfo],
+    rects: &[Option<Rect>],
+    visible: &[usize],
+) {
+    let mut groups: BTreeMap<usize, Vec<Pos2>> = BTreeMap::new();
+
+    for &index in visible {
+        if let Some(rect) = rects[index] {
+            let group = groups.entry(top_level_ancestor(nodes, index)).or_default();
+            group.extend([
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ]);
+        }
+    }
+
+    for (index, points) in groups.into_values().enumerate() {
+        let hull = convex_hull(points);
+        if hull.len() < 3 {
+            continue;
+        }
+        let color = group_color(index);
+        painter.convex_polygon(hull, color, Stroke::new(1.0, color.to_opaque()));
+    }
+}
+
+/// The color of the tree loaded as the `index`th `--overlay`.
+pub fn overlay_color(index: usize) -> Color32 {
+    // Offset from the hull hues so the first overlay does not match the first hull.
+    Hsva::new(group_hue(index + 1), 0.9, 1.0, 1.0).into()
+}
+
+/// Outlines every node of an overlaid tree in `color`.
+pub fn paint_overlay(
+    painter: &mut dyn Renderer,
+    nodes: &[NodeInfo],
+    calibration: &Calibration,
+    color: Color32,
+    rounding: f32,
+) {
+    for extents in nodes.iter().filter_map(|node| node.extents) {
+        if extents.width > 0 && extents.height > 0 {
+            painter.rect_stroke(
+                calibration.apply(extents.rect()),
+                rounding,
+                Stroke::new(1.5, color),
+                StrokeKind::Inside,
+            );
+        }
+    }
+}
+
+/// Lists which color each overlaid tree or application is drawn in, below the application
+/// name in the top-left corner of `screen`.
+pub fn paint_legend(painter: &mut dyn Renderer, screen: Rect, entries: &[(String, Color32)]) {
+    let font = FontId::proportional(14.0);
+    let mut top = screen.top() + 40.0;
+    for (source, color) in entries {
+        let size = painter.text_size(source, &font, f32::INFINITY);
+        let swatch = Rect::from_min_size(
+            egui::pos2(screen.left() + 8.0, top),
+            egui::vec2(size.y, size.y),
+        );
+        let min = egui::pos2(swatch.right() + 6.0, top);
+        let background = swatch.union(Rect::from_min_size(min, size)).expand(4.0);
+        painter.rect_filled(background, 4.0, Color32::from_black_alpha(180));
+        painter.rect_filled(swatch, 2.0, *color);
+        painter.text(
+            min,
+            Align2::LEFT_TOP,
+            source,
+            font.clone(),
+            Color32::WHITE,
+            f32::INFINITY,
+        );
+        top = background.bottom() + 4.0;
+    }
+}
+
+/// Shows which application and toolkit the tree comes from in the top-right corner of
+/// `screen`.
+pub fn paint_toolkit(painter: &mut dyn Renderer, screen: Rect, toolkit: &ToolkitInfo) {
+    let text = format!(
+        "{}\n{} {} (AT-SPI {})",
+        toolkit.application, toolkit.toolkit, toolkit.version, toolkit.atspi_version
+    );
+    let font = FontId::proportional(14.0);
+    let size = painter.text_size(&text, &font, f32::INFINITY);
+    let min = screen.right_top() + egui::vec2(-8.0 - size.x, 8.0);
+    let rect = Rect::from_min_size(min, size).expand(4.0);
+    painter.rect_filled(rect, 4.0, Color32::from_black_alpha(180));
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        &text,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Shows a short status such as the recording indicator in the bottom-right corner of
+/// `screen`.
+pub fn paint_status(painter: &mut dyn Renderer, screen: Rect, status: &str, color: Color32) {
+    let font = FontId::proportional(16.0);
+    let size = painter.text_size(status, &font, f32::INFINITY);
+    let min = screen.right_bottom() - size - egui::vec2(8.0, 8.0);
+    let rect = Rect::from_min_size(min, size).expand(4.0);
+    painter.rect_filled(rect, 4.0, Color32::from_black_alpha(180));
+    painter.text(min, Align2::LEFT_TOP, status, font, color, f32::INFINITY);
+}
+
+/// Shows a brief confirmation centered near the bottom of `screen`.
+pub fn paint_toast(painter: &mut dyn Renderer, screen: Rect, toast: &str) {
+    let font = FontId::proportional(16.0);
+    let size = painter.text_size(toast, &font, f32::INFINITY);
+    let min = screen.center_bottom() - egui::vec2(size.x / 2.0, size.y + 48.0);
+    let rect = Rect::from_min_size(min, size).expand(6.0);
+    painter.rect_filled(rect, 4.0, Color32::from_black_alpha(200));
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        toast,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines the focused top-level window and labels it with its name.
+pub fn paint_focused_window(
+    painter: &mut dyn Renderer,
+    window: &NodeInfo,
+    calibration: &Calibration,
+    rounding: f32,
+) {
+    let Some(extents) = window.extents else {
+        return;
+    };
+    let rect = calibration.apply(extents.rect());
+    painter.rect_stroke(
+        rect,
+        rounding,
+        Stroke::new(4.0, Color32::YELLOW),
+        StrokeKind::Inside,
+    );
+    painter.text(
+        rect.left_top() + egui::vec2(6.0, 6.0),
+        Align2::LEFT_TOP,
+        &window.name,
+        FontId::proportional(16.0),
+        Color32::YELLOW,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines an image node, green if it has a text alternative and with a labelled
+/// warning color if it has none.
+pub fn paint_image(painter: &mut dyn Renderer, rect: Rect, node: &NodeInfo, rounding: f32) {
+    if node.has_text_alternative() {
+        painter.rect_stroke(
+            rect,
+            rounding,
+            Stroke::new(2.0, Color32::GREEN),
+            StrokeKind::Inside,
+        );
+        return;
+    }
+
+    let warning = Color32::from_rgb(255, 100, 0);
+    painter.rect_filled(rect, rounding, warning.gamma_multiply(0.3));
+    painter.rect_stroke(
+        rect,
+        rounding,
+        Stroke::new(3.0, warning),
+        StrokeKind::Inside,
+    );
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        "no alt text",
+        FontId::proportional(12.0),
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines and fills a node matching the search.
+pub fn paint_search_match(painter: &mut dyn Renderer, rect: Rect, rounding: f32) {
+    let color = Color32::from_rgb(120, 255, 60);
+    painter.rect_filled(rect, rounding, color.gamma_multiply(0.25));
+    painter.rect_stroke(rect, rounding, Stroke::new(2.5, color), StrokeKind::Outside);
+}
+
+/// Outlines a node that was not in the marked baseline and tags it as new.
+pub fn paint_new(painter: &mut dyn Renderer, rect: Rect, rounding: f32) {
+    let color = Color32::from_rgb(0, 230, 170);
+    painter.rect_stroke(rect, rounding, Stroke::new(2.0, color), StrokeKind::Outside);
+    let font = FontId::proportional(10.0);
+    let size = painter.text_size("new", &font, f32::INFINITY);
+    let min = rect.right_top() - egui::vec2(size.x, 0.0);
+    painter.rect_filled(Rect::from_min_size(min, size).expand(1.0), 2.0, color);
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        "new",
+        font,
+        Color32::BLACK,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines a tab stop, filling it if it currently has focus.
+pub fn paint_tab_stop(painter: &mut dyn Renderer, rect: Rect, focused: bool, rounding: f32) {
+    let color = Color32::from_rgb(0, 200, 255);
+    if focused {
+        painter.rect_filled(rect, rounding, color.gamma_multiply(0.35));
+    }
+    painter.rect_stroke(rect, rounding, Stroke::new(2.0, color), StrokeKind::Inside);
+}
+
+/// Outlines one of several nodes that claim focus at the same time with a dashed border.
+pub fn paint_focus_conflict(painter: &mut dyn Renderer, rect: Rect, rounding: f32) {
+    let color = Color32::from_rgb(255, 0, 200);
+    painter.rect_filled(rect, rounding, color.gamma_multiply(0.15));
+    painter.patterned_rect_stroke(rect, rounding, Stroke::new(3.0, color), LinePattern::Dashed);
+}
+
+/// Points from the edge of `area` towards a focusable node whose `rect` lies outside it,
+/// with the node's `label` next to the arrowhead.
+pub fn paint_offscreen(painter: &mut dyn Renderer, area: Rect, rect: Rect, label: &str) {
+    let color = Color32::from_rgb(230, 150, 0);
+    let tip = area.shrink(4.0).clamp(rect.center());
+    let direction = (rect.center() - tip).normalized();
+    let base = tip - direction * 14.0;
+    let side = direction.rot90() * 7.0;
+    painter.convex_polygon(vec![tip, base + side, base - side], color, Stroke::NONE);
+
+    // The label extends inwards from the arrow, away from the edges it points past.
+    let align = |component: f32| match component {
+        c if c > 0.5 => Align::Max,
+        c if c < -0.5 => Align::Min,
+        _ => Align::Center,
+    };
+    let anchor = Align2([align(direction.x), align(direction.y)]);
+    let font = FontId::proportional(11.0);
+    painter.text(
+        base - direction * 4.0,
+        anchor,
+        label,
+        font,
+        color,
+        f32::INFINITY,
+    );
+}
+
+/// Notes along the bottom of `rect` how many of a node's `children` were left out of the
+/// traversal beyond the `max_children` collected.
+pub fn paint_truncated(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    children: usize,
+    max_children: usize,
+) {
+    let text = format!("+{} more children", children.saturating_sub(max_children));
+    let font = FontId::proportional(11.0);
+    let size = painter.text_size(&text, &font, f32::INFINITY);
+    let min = rect.center_bottom() - egui::vec2(size.x / 2.0, size.y + 2.0);
+    painter.rect_filled(
+        Rect::from_min_size(min, size).expand(2.0),
+        2.0,
+        Color32::from_rgb(200, 80, 0).gamma_multiply(0.9),
+    );
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        &text,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines the selected children of a container so they stand out from the others.
+pub fn paint_selection(
+    painter: &mut dyn Renderer,
+    selection: &SelectionInfo,
+    calibration: &Calibration,
+    rounding: f32,
+) {
+    let color = Color32::from_rgb(255, 120, 200);
+    for extents in &selection.selected {
+        let rect = calibration.apply(extents.rect());
+        painter.rect_filled(rect, rounding, color.gamma_multiply(0.2));
+        painter.rect_stroke(rect, rounding, Stroke::new(2.0, color), StrokeKind::Inside);
+    }
+}
+
+/// Underlines the text of each link in `links`, with a dot where the link starts; links
+/// without extents are skipped.
+pub fn paint_hyperlinks(
+    painter: &mut dyn Renderer,
+    links: &[Hyperlink],
+    calibration: &Calibration,
+) {
+    let color = Color32::from_rgb(0, 160, 255);
+    for link in links {
+        let Some(extents) = link.extents else {
+            continue;
+        };
+        let rect = calibration.apply(extents.rect());
+        painter.rect_filled(rect, 0.0, color.gamma_multiply(0.15));
+        painter.line(
+            rect.left_bottom(),
+            rect.right_bottom(),
+            Stroke::new(2.0, color),
+        );
+        painter.circle_filled(rect.left_bottom(), 3.0, color);
+    }
+}
+
+/// Draws the cell grid of a table, filling headers so they stand out from cells, and
+/// labels its dimensions above `rect`.
+pub fn paint_table(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    table: &TableInfo,
+    calibration: &Calibration,
+    rounding: f32,
+) {
+    let cell_color = Color32::from_rgb(180, 120, 255);
+    let header_color = Color32::from_rgb(255, 160, 0);
+    for cell in &table.cells {
+        let cell_rect = calibration.apply(cell.extents.rect());
+        if cell.header {
+            painter.rect_filled(cell_rect, rounding, header_color.gamma_multiply(0.3));
+            painter.rect_stroke(
+                cell_rect,
+                rounding,
+                Stroke::new(1.5, header_color),
+                StrokeKind::Inside,
+            );
+        } else {
+            painter.rect_stroke(
+                cell_rect,
+                rounding,
+                Stroke::new(1.0, cell_color),
+                StrokeKind::Inside,
+            );
+        }
+    }
+
+    let text = format!("{} × {}", table.rows, table.columns);
+    let font = FontId::proportional(12.0);
+    let size = painter.text_size(&text, &font, f32::INFINITY);
+    let min = rect.right_top() - egui::vec2(size.x, size.y + 4.0);
+    painter.rect_filled(
+        Rect::from_min_size(min, size).expand(2.0),
+        2.0,
+        cell_color.gamma_multiply(0.8),
+    );
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        &text,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Stacks summary `lines`, each in its own color, in the bottom-left corner of
+/// `screen`.
+pub fn paint_summary(painter: &mut dyn Renderer, screen: Rect, lines: &[(String, Color32)]) {
+    let font = FontId::proportional(14.0);
+    let mut bottom = screen.bottom() - 8.0;
+    for (text, color) in lines.iter().rev() {
+        let size = painter.text_size(text, &font, f32::INFINITY);
+        let min = egui::pos2(screen.left() + 8.0, bottom - size.y);
+        let rect = Rect::from_min_size(min, size).expand(4.0);
+        painter.rect_filled(rect, 4.0, Color32::from_black_alpha(180));
+        painter.text(
+            min,
+            Align2::LEFT_TOP,
+            text,
+            font.clone(),
+            *color,
+            f32::INFINITY,
+        );
+        bottom = rect.top() - 4.0;
+    }
+}
+
+/// Outlines a node failing lint rules with a pulse and lists its issues below it, in
+/// amber if they are only warnings.
+pub fn paint_issues(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    finding: &Finding,
+    rounding: f32,
+    pulse: f32,
+) {
+    let color = if finding.warning {
+        Color32::from_rgb(230, 150, 0)
+    } else {
+        Color32::from_rgb(255, 60, 60)
+    };
+    painter.rect_stroke(
+        rect,
+        rounding,
+        pulsing_stroke(2.0, color, pulse),
+        StrokeKind::Outside,
+    );
+
+    let text = finding.issues.join("\n");
+    let font = FontId::proportional(12.0);
+    let size = painter.text_size(&text, &font, 400.0);
+    let min = rect.left_bottom() + egui::vec2(0.0, 4.0);
+    painter.rect_filled(
+        Rect::from_min_size(min, size).expand(3.0),
+        2.0,
+        color.gamma_multiply(0.8),
+    );
+    painter.text(min, Align2::LEFT_TOP, &text, font, Color32::WHITE, 400.0);
+}
+
+/// Fills `rect` to flag a node that just changed; `fade` runs from 1 down to 0 as the
+/// highlight expires.
+pub fn paint_flash(painter: &mut dyn Renderer, rect: Rect, fade: f32, rounding: f32) {
+    let alpha = (fade.clamp(0.0, 1.0) * 160.0) as u8;
+    painter.rect_filled(
+        rect,
+        rounding,
+        Color32::from_rgba_unmultiplied(255, 220, 0, alpha),
+    );
+}
+
+/// A stroke of `width` that thickens and fades as `pulse` goes from 0 to 1.
+fn pulsing_stroke(width: f32, color: Color32, pulse: f32) -> Stroke {
+    Stroke::new(
+        width * (1.0 + pulse),
+        color.gamma_multiply(1.0 - 0.4 * pulse),
+    )
+}
+
+/// Outlines the node selected for inspection with a pulse.
+pub fn paint_selected(
+    painter: &mut dyn Renderer,
+    node: &NodeInfo,
+    calibration: &Calibration,
+    rounding: f32,
+    pulse: f32,
+) {
+    let Some(extents) = node.extents else {
+        return;
+    };
+    painter.rect_stroke(
+        calibration.apply(extents.rect()),
+        rounding,
+        pulsing_stroke(2.0, Color32::from_rgb(0, 255, 255), pulse),
+        StrokeKind::Outside,
+    );
+}
+
+/// The color of `state` when coloring nodes by state.
+pub fn state_color(state: SignificantState) -> Color32 {
+    match state {
+        SignificantState::Focused => Color32::YELLOW,
+        SignificantState::Selected => Color32::LIGHT_BLUE,
+        SignificantState::Checked => Color32::GREEN,
+        SignificantState::Disabled => Color32::GRAY,
+    }
+}
+
+/// The color of `node`'s marker under `scheme`, given the `application` it belongs to.
+pub fn marker_color(node: &NodeInfo, application: &str, scheme: MarkerColors) -> Color32 {
+    match scheme {
+        MarkerColors::Uniform => Color32::RED,
+        MarkerColors::Role => Hsva::new(group_hue(node.role as usize), 0.8, 0.9, 1.0).into(),
+        MarkerColors::State => significant_state(node.states).map_or(Color32::RED, state_color),
+        MarkerColors::Application => {
+            let lightness = 0.6 + 0.35 * group_hue(node.role as usize);
+            Hsva::new(application_hue(application), 0.8, lightness, 1.0).into()
+        }
+    }
+}
+
+/// The color `application` is listed in the legend with under application marker colors.
+pub fn application_color(application: &str) -> Color32 {
+    Hsva::new(application_hue(application), 0.8, 0.9, 1.0).into()
+}
+
+/// Fills and outlines the box of a node as `style` asks. The outline is left to
+/// `paint_outline` or `paint_pattern` where they apply, so it is skipped when `outlined`.
+pub fn paint_box(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    color: Color32,
+    style: BoxStyle,
+    outlined: bool,
+    rounding: f32,
+) {
+    if style.fills() {
+        painter.rect_filled(rect, rounding, color.gamma_multiply(0.15));
+    }
+    if style.outlines() && !outlined {
+        let stroke = Stroke::new(1.0, color.gamma_multiply(0.7));
+        painter.rect_stroke(rect, rounding, stroke, StrokeKind::Inside);
+    }
+}
+
+/// Outlines a node in color-by-state mode.
+pub fn paint_outline(painter: &mut dyn Renderer, rect: Rect, color: Color32, rounding: f32) {
+    painter.rect_stroke(rect, rounding, Stroke::new(1.5, color), StrokeKind::Inside);
+}
+
+/// Outlines a node in `pattern` for pattern mode, hatching its box if the pattern asks
+/// for it.
+pub fn paint_pattern(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    color: Color32,
+    pattern: StatePattern,
+    rounding: f32,
+) {
+    if pattern.hatched {
+        painter.hatch(rect, Stroke::new(1.0, color.gamma_multiply(0.5)), 6.0);
+    }
+    painter.patterned_rect_stroke(rect, rounding, Stroke::new(1.5, color), pattern.line);
+}
+
+/// Draws the 10x10 marker identifying a node in the top-left corner of its `rect`.
+pub fn paint_marker(painter: &mut dyn Renderer, rect: Rect, color: Color32) {
+    let x_range = Rangef::new(rect.min.x, rect.min.x + 10.0);
+    let y_range = Rangef::new(rect.min.y, rect.min.y + 10.0);
+    painter.rect_filled(Rect::from_x_y_ranges(x_range, y_range), 0.0, color);
+}
+
+/// The font size, in the overlay's points, that shows labels at `logical_size` points on
+/// a display with the OS scale factor `native_pixels_per_point`.
+///
+/// egui already scales points by `pixels_per_point`, so this only corrects for the
+/// overlay rendering at a different scale than the display's, e.g. at 1.0 on a 2.0x
+/// screen to line up with physical-pixel extents; without that, labels would be half as
+/// tall as the rest of the desktop's text.
+pub fn label_font_size(
+    logical_size: f32,
+    native_pixels_per_point: f32,
+    pixels_per_point: f32,
+) -> f32 {
+    logical_size * native_pixels_per_point / pixels_per_point
+}
+
+/// Where a label of `size` goes relative to the node `rect`.
+///
+/// Placements inside the box fall back to `Above` when the label does not fit, so labels
+/// of tiny boxes stay readable.
+pub fn label_position(rect: Rect, size: Vec2, placement: LabelPlacement) -> Pos2 {
+    let above = Pos2::new(rect.min.x, rect.min.y - size.y - 2.0);
+    // Leaves room for the node marker in the top-left corner.
+    let corner_inset = egui::vec2(12.0, 1.0);
+    match placement {
+        LabelPlacement::Inside if size.x <= rect.width() && size.y <= rect.height() => {
+            rect.center() - size / 2.0
+        }
+        LabelPlacement::Corner
+            if size.x + corner_inset.x <= rect.width()
+                && size.y + corner_inset.y <= rect.height() =>
+        {
+            rect.min + corner_inset
+        }
+        _ => above,
+    }
+}
+
+/// Labels a node with its role and name.
+pub fn paint_label(
+    painter: &mut dyn Renderer,
+    rect: Rect,
+    node: &NodeInfo,
+    placement: LabelPlacement,
+    font_size: f32,
+) {
+    let label = node.label();
+    let font = FontId::proportional(font_size);
+    let size = painter.text_size(&label, &font, f32::INFINITY);
+    let pos = label_position(rect, size, placement);
+    painter.rect_filled(
+        Rect::from_min_size(pos, size).expand(1.0),
+        2.0,
+        Color32::from_black_alpha(170),
+    );
+    painter.text(
+        pos,
+        Align2::LEFT_TOP,
+        &label,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines the node under the pointer in hover mode and labels it with its role and
+/// name.
+pub fn paint_hovered(
+    painter: &mut dyn Renderer,
+    node: &NodeInfo,
+    calibration: &Calibration,
+    rounding: f32,
+) {
+    let Some(extents) = node.extents else {
+        return;
+    };
+    let rect = calibration.apply(extents.rect());
+    let color = Color32::from_rgb(255, 0, 255);
+    painter.rect_stroke(rect, rounding, Stroke::new(2.0, color), StrokeKind::Outside);
+
+    let label = node.label();
+    let font = FontId::proportional(14.0);
+    let size = painter.text_size(&label, &font, f32::INFINITY);
+    let min = rect.left_bottom() + egui::vec2(0.0, 4.0);
+    painter.rect_filled(
+        Rect::from_min_size(min, size).expand(3.0),
+        2.0,
+        Color32::from_black_alpha(200),
+    );
+    painter.text(
+        min,
+        Align2::LEFT_TOP,
+        &label,
+        font,
+        Color32::WHITE,
+        f32::INFINITY,
+    );
+}
+
+/// Outlines the screen region collection is limited to.
+pub fn paint_region(painter: &mut dyn Renderer, rect: Rect) {
+    painter.rect_stroke(
+        rect,
+        0.0,
+        Stroke::new(1.5, Color32::ORANGE),
+        StrokeKind::Outside,
+    );
+}
+
+/// Connects the centers of successively focused nodes with arrows and numbers each
+/// stop in focus order.
+pub fn paint_focus_trail<'a>(
+    painter: &mut dyn Renderer,
+    trail: impl IntoIterator<Item = &'a NodeInfo>,
+    calibration: &Calibration,
+) {
+    let stroke = Stroke::new(2.0, Color32::LIGHT_BLUE);
+    let centers: Vec<Pos2> = trail
+        .into_iter()
+        .filter_map(|node| node.extents)
+        .map(|extents| calibration.apply(extents.rect()).center())
+        .collect();
+
+    for pair in centers.windows(2) {
+        painter.arrow(pair[0], pair[1] - pair[0], stroke);
+    }
+    for (number, &center) in centers.iter().enumerate() {
+        painter.circle_filled(center, 9.0, Color32::from_black_alpha(200));
+        painter.text(
+            center,
+            Align2::CENTER_CENTER,
+            &(number + 1).to_string(),
+            FontId::proportional(11.0),
+            Color32::LIGHT_BLUE,
+            f32::INFINITY,
+        );
+    }
+}
+
+/// Draws a fill bar across `rect` showing where `value` sits in its range, labelled
+/// with the current value.
+pub fn paint_value(painter: &mut dyn Renderer, rect: Rect, value: &NodeValue, rounding: f32) {
+    let mut filled = rect;
+    filled.set_width(rect.width() * value.fraction());
+    painter.rect_filled(
+        filled,
+        rounding,
+        Color32::from_rgba_unmultiplied(0, 200, 0, 96),
+    );
+    painter.text(
+        rect.left_bottom(),
+        Align2::LEFT_TOP,
+        &value.current.to_string(),
+        FontId::monospace(12.0),
+        Color32::GREEN,
+        f32::INFINITY,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_each_state_apart() {
+        let colors = [
+            SignificantState::Focused,
+            SignificantState::Selected,
+            SignificantState::Checked,
+            SignificantState::Disabled,
+        ]
+        .map(state_color);
+        for (i, color) in colors.iter().enumerate() {
+            assert!(!colors[..i].contains(color), "{color:?} is used twice");
+        }
+    }
+
+    #[test]
+    fn keeps_label_size_at_the_native_scale() {
+        assert_eq!(label_font_size(12.0, 1.0, 1.0), 12.0);
+        assert_eq!(label_font_size(12.0, 2.0, 2.0), 12.0);
+    }
+
+    #[test]
+    fn scales_labels_of_an_overlay_at_another_scale() {
+        assert_eq!(label_font_size(12.0, 2.0, 1.0), 24.0);
+        assert_eq!(label_font_size(12.0, 1.0, 2.0), 6.0);
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::Role;
+use eframe::egui;
+use egui::Color32;
+use log::{Level, error};
+use std::collections::BTreeMap;
+
+use crate::command::{Command, HitTarget};
+use crate::config::HitTestStrategy;
+use crate::node::address;
+
+use super::ScreenPainterGUI;
+
+impl ScreenPainterGUI {
+    /// Lists the most recent errors and warnings, newest first.
+    pub(super) fn show_error_panel(&self, ctx: &egui::Context) {
+        let entries = self.errors.recent();
+
+        egui::Window::new(format!("Errors ({})", entries.len())).show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for entry in &entries {
+                        let color = match entry.level {
+                            Level::Error => Color32::LIGHT_RED,
+                            _ => Color32::YELLOW,
+                        };
+                        let age = entry.time.elapsed().as_secs();
+                        ui.colored_label(color, format!("[{age}s ago] {}", entry.message));
+                    }
+                });
+        });
+    }
+
+    /// Shows the on-screen controls while in interactive mode.
+    pub(super) fn show_controls(&mut self, ctx: &egui::Context) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let tree_depth = state.iter().map(|node| node.depth).max().unwrap_or(1);
+        let matches = state
+            .iter()
+            .filter(|node| self.search.matches(node))
+            .count();
+
+        egui::Window::new("Controls").show(ctx, |ui| {
+            let mut depth = self.max_depth.unwrap_or(tree_depth).min(tree_depth);
+            ui.add(egui::Slider::new(&mut depth, 1..=tree_depth).text("Max depth"));
+            self.max_depth = (depth < tree_depth).then_some(depth);
+
+            let before = self.config.hit_test;
+            ui.horizontal(|ui| {
+                ui.label("Hit test");
+                ui.radio_value(
+                    &mut self.config.hit_test,
+                    HitTestStrategy::AtPoint,
+                    "AT-SPI",
+                );
+                ui.radio_value(&mut self.config.hit_test, HitTestStrategy::Rects, "Rects");
+            });
+            if self.config.hit_test != before {
+                self.save_config();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Find");
+                ui.text_edit_singleline(&mut self.search.query);
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.search.names, "Names");
+                ui.checkbox(&mut self.search.values, "Values");
+                if self.search.is_active() {
+                    ui.label(format!("{matches} found"));
+                }
+            });
+        });
+    }
+
+    /// Lists the roles in the tree with their node counts, each with a checkbox showing
+    /// or hiding its nodes without re-collecting. Starts collapsed.
+    pub(super) fn show_roles_panel(&mut self, ctx: &egui::Context) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let mut counts: BTreeMap<&str, (Role, usize)> = BTreeMap::new();
+        for node in state {
+            counts.entry(node.role.name()).or_insert((node.role, 0)).1 += 1;
+        }
+        let hidden_roles = &mut self.hidden_roles;
+
+        egui::Window::new("Roles")
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("All").clicked() {
+                        hidden_roles.clear();
+                    }
+                    if ui.button("None").clicked() {
+                        hidden_roles.extend(counts.values().map(|(role, _)| *role));
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (name, (role, count)) in &counts {
+                            let mut shown = !hidden_roles.contains(role);
+                            if ui
+                                .checkbox(&mut shown, format!("{name} ({count})"))
+                                .changed()
+                            {
+                                if shown {
+                                    hidden_roles.remove(role);
+                                } else {
+                                    hidden_roles.insert(*role);
+                                }
+                            }
+                        }
+                    });
+            });
+    }
+
+    /// Lists the collected nodes without extents, e.g. those not implementing Component,
+    /// which the overlay cannot place; clicking one inspects it.
+    pub(super) fn show_no_geometry_panel(&mut self, ctx: &egui::Context) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let unplaced: Vec<_> = state.iter().filter(|node| node.extents.is_none()).collect();
+        let mut clicked = None;
+
+        egui::Window::new(format!("No geometry ({})", unplaced.len())).show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for node in unplaced {
+                        let indent = "  ".repeat(node.depth.saturating_sub(1));
+                        if ui.link(format!("{indent}{}", node.label())).clicked() {
+                            clicked = Some(node.clone());
+                        }
+                    }
+                });
+        });
+
+        if clicked.is_some() {
+            self.set_hit(HitTarget::Select, clicked);
+        }
+    }
+
+    /// Shows the path from the application down to the collection root; clicking an
+    /// ancestor collects its subtree instead.
+    pub(super) fn show_breadcrumb(&mut self, ctx: &egui::Context) {
+        let Some((_, crumbs)) = &self.ancestry else {
+            return;
+        };
+        let Some((root, ancestors)) = crumbs.split_last() else {
+            return;
+        };
+
+        let mut clicked = None;
+        egui::Area::new(egui::Id::new("breadcrumb"))
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for crumb in ancestors {
+                            if ui.link(&crumb.label).clicked() {
+                                clicked = Some(crumb.object.clone());
+                            }
+                            ui.label("›");
+                        }
+                        ui.strong(&root.label);
+                    });
+                });
+            });
+        if let Some(object) = clicked {
+            self.navigate(object);
+        }
+    }
+
+    /// Shows a slider scrubbing through the frames of a replayed session, labelled with
+    /// each frame's time since the first.
+    pub(super) fn show_timeline(&mut self, ctx: &egui::Context) {
+        let start = self.replay[0].timestamp;
+        let last = self.replay.len() - 1;
+        let mut index = self.replay_index;
+
+        egui::Area::new(egui::Id::new("timeline"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -8.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.spacing_mut().slider_width = 600.0;
+                    let replay = &self.replay;
+                    ui.add(
+                        egui::Slider::new(&mut index, 0..=last)
+                            .custom_formatter(|index, _| {
+                                let elapsed = replay[index as usize].timestamp - start;
+                                format!("{} (+{elapsed:.1}s)", index as usize + 1)
+                            })
+                            .custom_parser(|text| {
+                                let frame: f64 = text.split_whitespace().next()?.parse().ok()?;
+                                Some(frame - 1.0)
+                            })
+                            .text("Frame"),
+                    );
+                });
+            });
+
+        if index != self.replay_index {
+            self.show_frame(index);
+        }
+    }
+
+    /// Shows the details of the selected node.
+    pub(super) fn show_inspector(&mut self, ctx: &egui::Context) {
+        let Some(node) = &self.selected else {
+            return;
+        };
+        let mut open = true;
+        let mut commands = Vec::new();
+        let mut pending_action = self.pending_action;
+        let mut toggle_solo = false;
+
+        egui::Window::new("Inspector")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+                    ui.label("Role");
+                    ui.label(node.role.name());
+                    ui.end_row();
+                    ui.label("Name");
+                    ui.label(&node.name);
+                    ui.end_row();
+                    ui.label("Description");
+                    ui.label(&node.description);
+                    ui.end_row();
+                    ui.label("Address");
+                    ui.label(address(&node.object));
+                    ui.end_row();
+                    ui.label("Depth");
+                    ui.label(node.depth.to_string());
+                    ui.end_row();
+                    if let Some(extents) = node.extents {
+                        ui.label("Extents");
+                        ui.label(format!(
+                            "{}x{} at ({}, {})",
+                            extents.width, extents.height, extents.x, extents.y
+                        ));
+                        ui.end_row();
+                        if let Some(both) = node.both_extents {
+                            for (label, extents) in
+                                [("Screen", both.screen), ("Window", both.window)]
+                            {
+                                ui.label(label);
+                                ui.label(format!(
+                                    "{}x{} at ({}, {})",
+                                    extents.width, extents.height, extents.x, extents.y
+                                ));
+                                ui.end_row();
+                            }
+                        }
+                        if let Some((window, origin)) = &self.origin {
+                            ui.label("Relative");
+                            ui.label(format!(
+                                "({}, {}) in {window}",
+                                extents.x - origin.x as i32,
+                                extents.y - origin.y as i32
+                            ));
+                            ui.end_row();
+                        }
+                    }
+                    if let Some(children) = node.truncated_children {
+                        ui.label("Children");
+                        let collected = match self.config.traversal.max_children {
+                            Some(max_children) => format!("first {max_children} of {children}"),
+                            None => format!("some of {children}"),
+                        };
+                        ui.label(format!("{collected} traversed"));
+                        ui.end_row();
+                    }
+                    if let Some(contrast) = &node.contrast {
+                        let [r, g, b] = contrast.foreground;
+                        let [br, bg, bb] = contrast.background;
+                        ui.label("Contrast");
+                        ui.label(format!(
+                            "{:.2}:1 (#{r:02x}{g:02x}{b:02x} on #{br:02x}{bg:02x}{bb:02x})",
+                            contrast.ratio
+                        ));
+                        ui.end_row();
+                    }
+                    if let Some(selection) = &node.selection {
+                        ui.label("Selected");
+                        ui.label(format!("{} children", selection.count));
+                        ui.end_row();
+                    }
+                    if let Some(links) = &node.hyperlinks {
+                        ui.label("Links");
+                        ui.vertical(|ui| {
+                            ui.label(links.len().to_string());
+                            for link in links {
+                                ui.label(format!("{}..{} {}", link.start, link.end, link.uri));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                    if let Some(text) = &node.text {
+                        ui.label("Text");
+                        ui.label(text);
+                        ui.end_row();
+                    }
+                    if let Some(value) = node.value {
+                        ui.label("Value");
+                        ui.label(format!(
+                            "{} in {}..={}",
+                            value.current, value.minimum, value.maximum
+                        ));
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                match &node.interfaces {
+                    Some(interfaces) => {
+                        ui.label("Interfaces");
+                        for interface in interfaces {
+                            ui.label(format!("{interface:?}"));
+                        }
+                    }
+                    None => {
+                        if ui.button("Query interfaces").clicked() {
+                            commands.push(Command::QueryInterfaces(node.object.clone()));
+                        }
+                    }
+                }
+
+                ui.separator();
+                match &node.actions {
+                    Some(actions) if actions.is_empty() => {
+                        ui.label("No actions");
+                    }
+                    Some(actions) => {
+                        ui.label("Actions");
+                        for (index, action) in actions.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.button(&action.name).clicked() {
+                                    pending_action = Some(index);
+                                }
+                                ui.label(&action.description);
+                            });
+                        }
+                    }
+                    None => {
+                        if ui.button("Query actions").clicked() {
+                            commands.push(Command::QueryActions(node.object.clone()));
+                        }
+                    }
+                }
+
+                // Invoking an action acts on the target application, so it is confirmed
+                // separately from picking it.
+                if let Some(index) = pending_action
+                    && let Some(action) = node.actions.as_ref().and_then(|a| a.get(index))
+                {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "Invoke '{}' on '{}'? This acts on the application.",
+                            action.name, node.name
+                        ),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Invoke").clicked() {
+                            commands.push(Command::InvokeAction {
+                                object: node.object.clone(),
+                                index: index as i32,
+                                name: action.name.clone(),
+                            });
+                            pending_action = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            pending_action = None;
+                        }
+                    });
+                }
+
+                ui.separator();
+                let soloed = self.solo.as_ref() == Some(&node.object);
+                if ui.selectable_label(soloed, "Solo subtree").clicked() {
+                    toggle_solo = true;
+                }
+                if ui.button("Copy JSON").clicked() {
+                    match serde_json::to_string_pretty(node) {
+                        Ok(json) => ctx.copy_text(json),
+                        Err(err) => error!("Error serializing node: {err}"),
+                    }
+                }
+            });
+
+        for command in commands {
+            self.send_command(command);
+        }
+        self.pending_action = pending_action;
+        if toggle_solo {
+            self.toggle_solo();
+        }
+        if !open {
+            self.selected = None;
+            self.pending_action = None;
+        }
+    }
+}
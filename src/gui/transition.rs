@@ -0,0 +1,66 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::ObjectRef;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::node::{Extents, NodeInfo};
+
+/// The animation from one collected tree to the next: nodes still present move from
+/// their old extents to their new ones, new nodes fade in, and removed ones fade out.
+/// Nodes are matched by their object reference.
+#[derive(Debug)]
+pub struct Transition {
+    started: Instant,
+    duration: Duration,
+    /// The extents every node of the previous tree had.
+    from: HashMap<ObjectRef, Option<Extents>>,
+    /// Nodes of the previous tree missing from the new one, known once it is complete.
+    pub removed: Vec<NodeInfo>,
+}
+
+impl Transition {
+    /// Starts animating away from the `previous` tree.
+    pub fn new(previous: &[NodeInfo], duration: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            duration,
+            from: previous
+                .iter()
+                .map(|node| (node.object.clone(), node.extents))
+                .collect(),
+            removed: Vec::new(),
+        }
+    }
+
+    /// Records the nodes of `previous` that the complete new tree `current` lacks.
+    pub fn finish(&mut self, previous: &[NodeInfo], current: &[NodeInfo]) {
+        let current: HashSet<&ObjectRef> = current.iter().map(|node| &node.object).collect();
+        self.removed = previous
+            .iter()
+            .filter(|node| !current.contains(&node.object))
+            .cloned()
+            .collect();
+    }
+
+    /// How far along the animation is, eased from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let linear = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        };
+        linear * linear * (3.0 - 2.0 * linear)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    /// Where `node` was in the previous tree: `Some(None)` if it had no extents there,
+    /// `None` if it is new.
+    pub fn origin(&self, node: &NodeInfo) -> Option<Option<Extents>> {
+        self.from.get(&node.object).copied()
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use egui::{InputState, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Something done with a hotkey; see the hotkey table in the readme. The calibration
+/// keys are fixed.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, EnumIter,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hotkey {
+    ToggleOverlay,
+    NextApplication,
+    PreviousApplication,
+    ClearFocusTrail,
+    ToggleHulls,
+    ToggleSolo,
+    LiftSolo,
+    Zoom,
+    ZoomOut,
+    UndoRoot,
+    RedoRoot,
+    CopyAddress,
+    ToggleOrigin,
+    Save,
+    Capture,
+    ToggleLint,
+    ToggleLabels,
+    ToggleImages,
+    ToggleRecording,
+    PreviousFrame,
+    NextFrame,
+    CycleMarkerColors,
+    ToggleTabStops,
+    ToggleChanges,
+    ToggleNoGeometry,
+    ToggleErrors,
+    ReleaseDocument,
+    ResetRegion,
+    ToggleInteractive,
+    ToggleHover,
+    MarkBaseline,
+    ClearBaseline,
+}
+
+impl Hotkey {
+    /// The key bound to the action unless the config says otherwise.
+    fn default_binding(self) -> KeyCombo {
+        let key = |key| KeyCombo {
+            modifiers: Modifiers::NONE,
+            key,
+        };
+        match self {
+            Self::ToggleOverlay => key(Key::O),
+            Self::NextApplication => key(Key::N),
+            Self::PreviousApplication => key(Key::P),
+            Self::ClearFocusTrail => key(Key::T),
+            Self::ToggleHulls => key(Key::H),
+            Self::ToggleSolo => key(Key::X),
+            Self::LiftSolo => key(Key::Escape),
+            Self::Zoom => key(Key::Z),
+            Self::ZoomOut => key(Key::Backspace),
+            Self::UndoRoot => KeyCombo {
+                modifiers: Modifiers::COMMAND,
+                key: Key::Z,
+            },
+            Self::RedoRoot => KeyCombo {
+                modifiers: Modifiers::COMMAND | Modifiers::SHIFT,
+                key: Key::Z,
+            },
+            Self::CopyAddress => key(Key::Y),
+            Self::ToggleOrigin => key(Key::G),
+            Self::Save => key(Key::S),
+            Self::Capture => key(Key::D),
+            Self::ToggleLint => key(Key::L),
+            Self::ToggleLabels => key(Key::B),
+            Self::ToggleImages => key(Key::A),
+            Self::ToggleRecording => key(Key::W),
+            Self::PreviousFrame => key(Key::OpenBracket),
+            Self::NextFrame => key(Key::CloseBracket),
+            Self::CycleMarkerColors => key(Key::K),
+            Self::ToggleTabStops => key(Key::F),
+            Self::ToggleChanges => key(Key::C),
+            Self::ToggleNoGeometry => key(Key::J),
+            Self::ToggleErrors => key(Key::E),
+            Self::ReleaseDocument => key(Key::U),
+            Self::ResetRegion => key(Key::R),
+            Self::ToggleInteractive => key(Key::I),
+            Self::ToggleHover => key(Key::V),
+            Self::MarkBaseline => key(Key::M),
+            Self::ClearBaseline => KeyCombo {
+                modifiers: Modifiers::SHIFT,
+                key: Key::M,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Hotkey {
+    /// The action's name in the config, e.g. `toggle-solo`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in format!("{self:?}").chars().enumerate() {
+            if c.is_ascii_uppercase() && i > 0 {
+                write!(f, "-")?;
+            }
+            write!(f, "{}", c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// The keys calibration reads whatever modifiers are held, see the readme's hotkey table;
+/// no action can be bound to them.
+pub const CALIBRATION_KEYS: [Key; 8] = [
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::ArrowDown,
+    Key::Plus,
+    Key::Equals,
+    Key::Minus,
+    Key::Num0,
+];
+
+/// A key with the modifiers held down for it, written like `Ctrl+Shift+Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl KeyCombo {
+    fn shortcut(self) -> KeyboardShortcut {
+        KeyboardShortcut::new(self.modifiers, self.key)
+    }
+
+    /// How many modifiers the combination needs.
+    fn modifier_count(self) -> usize {
+        let Modifiers {
+            alt, ctrl, shift, ..
+        } = self.modifiers;
+        [alt, ctrl || self.modifiers.command, shift]
+            .into_iter()
+            .filter(|&held| held)
+            .count()
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(combo: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+        // A trailing empty part is the plus key itself, e.g. `Ctrl++`.
+        if combo.ends_with("++") || combo == "+" {
+            parts.retain(|part| !part.is_empty());
+            parts.push("+");
+        }
+        let Some((name, modifier_names)) = parts.split_last() else {
+            return Err(format!("Empty key combination '{combo}'"));
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for modifier in modifier_names {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "command" => Modifiers::COMMAND,
+                "shift" => Modifiers::SHIFT,
+                "alt" => Modifiers::ALT,
+                _ => return Err(format!("Unknown modifier '{modifier}' in '{combo}'")),
+            };
+        }
+        // Letters are named in upper case, but are as easily written in lower case.
+        let key = Key::from_name(name)
+            .or_else(|| Key::from_name(&name.to_ascii_uppercase()))
+            .ok_or_else(|| format!("Unknown key '{name}' in '{combo}'"))?;
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(combo: String) -> Result<Self, Self::Error> {
+        combo.parse()
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.to_string()
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.ctrl || self.modifiers.command {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+/// The key bound to each action: the config's `[keys]` table, with the defaults for the
+/// actions it leaves out. Only the rebound actions are saved back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<Hotkey, KeyCombo>",
+    into = "BTreeMap<Hotkey, KeyCombo>"
+)]
+pub struct KeyBindings {
+    bindings: BTreeMap<Hotkey, KeyCombo>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Hotkey::iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Takes the key presses of this frame that trigger an action, returning the actions.
+    ///
+    /// Combinations needing more modifiers are checked first and consume their key, so
+    /// e.g. `Ctrl+Z` does not also trigger an action bound to `Z`.
+    pub fn pressed(&self, input: &mut InputState) -> Vec<Hotkey> {
+        let mut bindings: Vec<(&Hotkey, &KeyCombo)> = self.bindings.iter().collect();
+        bindings.sort_by_key(|(_, combo)| std::cmp::Reverse(combo.modifier_count()));
+        bindings
+            .into_iter()
+            .filter(|(_, combo)| input.consume_shortcut(&combo.shortcut()))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+}
+
+impl TryFrom<BTreeMap<Hotkey, KeyCombo>> for KeyBindings {
+    type Error = String;
+
+    fn try_from(overrides: BTreeMap<Hotkey, KeyCombo>) -> Result<Self, Self::Error> {
+        let mut keys = Self::default();
+        keys.bindings.extend(overrides);
+
+        if let Some((action, combo)) = keys
+            .bindings
+            .iter()
+            .find(|(_, combo)| CALIBRATION_KEYS.contains(&combo.key))
+        {
+            return Err(format!(
+                "{combo} is bound to {action}, but {} is a calibration key",
+                combo.key.name()
+            ));
+        }
+
+        let mut bound: Vec<(&Hotkey, &KeyCombo)> = keys.bindings.iter().collect();
+        bound.sort_by_key(|(_, combo)| combo.to_string());
+        for pair in bound.windows(2) {
+            let [(first, combo), (second, other)] = pair else {
+                continue;
+            };
+            if combo == other {
+                return Err(format!("{combo} is bound to both {first} and {second}",));
+            }
+        }
+        Ok(keys)
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<Hotkey, KeyCombo> {
+    fn from(keys: KeyBindings) -> Self {
+        keys.bindings
+            .into_iter()
+            .filter(|(action, combo)| *combo != action.default_binding())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Event;
+
+    fn combo(combo: &str) -> KeyCombo {
+        combo.parse().unwrap()
+    }
+
+    #[test]
+    fn round_trips_key_combos() {
+        for written in [
+            "Z",
+            "Ctrl+Z",
+            "Ctrl+Shift+Z",
+            "Alt+Shift+F1",
+            "Escape",
+            "Ctrl+Plus",
+        ] {
+            assert_eq!(combo(written).to_string(), written);
+            assert_eq!(combo(&combo(written).to_string()), combo(written));
+        }
+    }
+
+    #[test]
+    fn parses_key_combos_loosely() {
+        assert_eq!(combo("ctrl + shift + z"), combo("Ctrl+Shift+Z"));
+        assert_eq!(combo("Cmd+Z"), combo("Ctrl+Z"));
+        assert_eq!(combo("Ctrl++"), combo("Ctrl+Plus"));
+        assert_eq!(combo("+").key, Key::Plus);
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        assert_eq!(
+            "Hyper+Z".parse::<KeyCombo>(),
+            Err("Unknown modifier 'Hyper' in 'Hyper+Z'".into())
+        );
+        assert_eq!(
+            "Ctrl+Nope".parse::<KeyCombo>(),
+            Err("Unknown key 'Nope' in 'Ctrl+Nope'".into())
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_bindings() {
+        let bind =
+            |action, written| KeyBindings::try_from(BTreeMap::from([(action, combo(written))]));
+        assert_eq!(
+            bind(Hotkey::ToggleHulls, "Z"),
+            Err("Z is bound to both toggle-hulls and zoom".into())
+        );
+        assert_eq!(
+            bind(Hotkey::ToggleHulls, "Shift+ArrowLeft"),
+            Err("Shift+Left is bound to toggle-hulls, but Left is a calibration key".into())
+        );
+        assert!(bind(Hotkey::ToggleHulls, "Ctrl+H").is_ok());
+    }
+
+    /// The actions `keys` triggers for a press of `combo`.
+    fn press(keys: &KeyBindings, combo: KeyCombo) -> Vec<Hotkey> {
+        let mut input = InputState::default();
+        input.modifiers = combo.modifiers;
+        input.events.push(Event::Key {
+            key: combo.key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: combo.modifiers,
+        });
+        keys.pressed(&mut input)
+    }
+
+    #[test]
+    fn triggers_the_binding_with_more_modifiers_first() {
+        let keys = KeyBindings::default();
+        assert_eq!(press(&keys, combo("M")), [Hotkey::MarkBaseline]);
+        assert_eq!(press(&keys, combo("Shift+M")), [Hotkey::ClearBaseline]);
+        assert_eq!(press(&keys, combo("Ctrl+Z")), [Hotkey::UndoRoot]);
+        assert_eq!(press(&keys, combo("Ctrl+Shift+Z")), [Hotkey::RedoRoot]);
+    }
+
+    #[test]
+    fn binds_every_hotkey_by_default() {
+        let keys = KeyBindings::default();
+        for action in Hotkey::iter() {
+            assert_eq!(press(&keys, action.default_binding()), [action], "{action}");
+        }
+    }
+}
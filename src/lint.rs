@@ -0,0 +1,613 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::{Role, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::LintConfig;
+use crate::node::{Extents, NodeInfo, is_ancestor};
+
+/// A heuristic flagging a common accessibility problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// An interactive node without an accessible name.
+    EmptyName,
+    /// An image or icon without a name or description.
+    MissingAltText,
+    /// A focusable node that takes up no space on screen.
+    ZeroSizeFocusable,
+    /// Two unrelated interactive nodes whose boxes overlap.
+    OverlappingClickable,
+    /// An interactive node inside another one, e.g. a link inside a button; both are
+    /// flagged.
+    NestedInteractive,
+    /// Siblings sharing the same non-blank name, which screen readers cannot tell apart.
+    DuplicateSiblingName,
+    /// An interactive node smaller than `LintConfig::min_target_size` in either
+    /// dimension, which is hard to hit with a pointer or finger. Only a warning.
+    TinyTarget,
+    /// A text node whose colors contrast less than `LintConfig::min_contrast`; only
+    /// checked when the traversal queries contrast.
+    LowContrast,
+    /// A tab stop whose box lies entirely off `LintConfig::screen`, so focus moving to it
+    /// cannot be seen; often a focus order bug. Only a warning.
+    OffscreenFocusable,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 9] = [
+        LintRule::EmptyName,
+        LintRule::MissingAltText,
+        LintRule::ZeroSizeFocusable,
+        LintRule::OverlappingClickable,
+        LintRule::NestedInteractive,
+        LintRule::DuplicateSiblingName,
+        LintRule::TinyTarget,
+        LintRule::LowContrast,
+        LintRule::OffscreenFocusable,
+    ];
+
+    /// Checks the node at `index` of `nodes`, indexed by `tree`, returning a description of
+    /// the issue if it fails.
+    pub fn check(
+        self,
+        nodes: &[NodeInfo],
+        index: usize,
+        tree: &TreeIndex,
+        config: &LintConfig,
+    ) -> Option<String> {
+        match self {
+            LintRule::EmptyName => empty_name(&nodes[index]),
+            LintRule::MissingAltText => missing_alt_text(&nodes[index]),
+            LintRule::ZeroSizeFocusable => zero_size_focusable(&nodes[index]),
+            LintRule::OverlappingClickable => overlapping_clickable(nodes, index, tree),
+            LintRule::NestedInteractive => nested_interactive(nodes, index, tree),
+            LintRule::DuplicateSiblingName => duplicate_sibling_name(nodes, index, tree),
+            LintRule::TinyTarget => tiny_target(&nodes[index], config.min_target_size),
+            LintRule::LowContrast => low_contrast(&nodes[index], config.min_contrast),
+            LintRule::OffscreenFocusable => config
+                .screen
+                .and_then(|screen| offscreen_focusable(&nodes[index], &screen)),
+        }
+    }
+
+    /// Whether failing the rule is only worth a warning rather than an error.
+    pub fn is_warning(self) -> bool {
+        matches!(self, LintRule::TinyTarget | LintRule::OffscreenFocusable)
+    }
+}
+
+/// Whether users are expected to operate `node`, either because it can take focus or
+/// because of its role.
+pub fn is_interactive(node: &NodeInfo) -> bool {
+    node.states.contains(State::Focusable)
+        || matches!(
+            node.role,
+            Role::Button
+                | Role::CheckBox
+                | Role::CheckMenuItem
+                | Role::ComboBox
+                | Role::Entry
+                | Role::Link
+                | Role::MenuItem
+                | Role::PasswordText
+                | Role::RadioButton
+                | Role::RadioMenuItem
+                | Role::Slider
+                | Role::SpinButton
+                | Role::ToggleButton
+        )
+}
+
+pub fn empty_name(node: &NodeInfo) -> Option<String> {
+    (is_interactive(node) && node.name.trim().is_empty())
+        .then(|| format!("{} has no accessible name", node.role.name()))
+}
+
+pub fn missing_alt_text(node: &NodeInfo) -> Option<String> {
+    (node.is_image() && !node.has_text_alternative())
+        .then(|| format!("{} has no name or description", node.role.name()))
+}
+
+pub fn zero_size_focusable(node: &NodeInfo) -> Option<String> {
+    let extents = node.extents?;
+    (node.states.contains(State::Focusable) && (extents.width <= 0 || extents.height <= 0)).then(
+        || {
+            format!(
+                "focusable {} is {}x{}",
+                node.role.name(),
+                extents.width,
+                extents.height
+            )
+        },
+    )
+}
+
+/// Flags interactive nodes narrower or shorter than `min_size` pixels. Nodes without any
+/// area are left to `zero_size_focusable`.
+pub fn tiny_target(node: &NodeInfo, min_size: f32) -> Option<String> {
+    let extents = node.extents.filter(Extents::has_size)?;
+    (is_interactive(node)
+        && ((extents.width as f32) < min_size || (extents.height as f32) < min_size))
+        .then(|| {
+            format!(
+                "{} is only {}x{}, below {min_size}x{min_size}",
+                node.role.name(),
+                extents.width,
+                extents.height
+            )
+        })
+}
+
+/// Flags tab stops whose box does not intersect `screen`. Nodes without any area are left
+/// to `zero_size_focusable`.
+pub fn offscreen_focusable(node: &NodeInfo, screen: &Extents) -> Option<String> {
+    let extents = node.extents.filter(Extents::has_size)?;
+    (node.is_tab_stop() && !extents.intersects(screen)).then(|| {
+        format!(
+            "focusable {} is off screen at ({}, {})",
+            node.role.name(),
+            extents.x,
+            extents.y
+        )
+    })
+}
+
+/// Flags nodes whose text contrasts less than `min_ratio` with its background.
+pub fn low_contrast(node: &NodeInfo, min_ratio: f64) -> Option<String> {
+    let contrast = node.contrast?;
+    (contrast.ratio < min_ratio)
+        .then(|| format!("contrast {:.2}:1, below {min_ratio}:1", contrast.ratio))
+}
+
+/// The nearest interactive ancestor of each node, worked out as nodes arrive in traversal
+/// order. Shared by `TreeIndex` and `CollectionStats`, so the nodes flagged and counted
+/// as nested agree.
+#[derive(Debug, Clone, Default)]
+pub struct InteractiveAncestry {
+    /// The nearest interactive ancestor-or-self of every node so far.
+    ancestors_or_self: Vec<Option<usize>>,
+}
+
+impl InteractiveAncestry {
+    /// Adds the next `node` in traversal order, returning its nearest interactive
+    /// ancestor.
+    pub fn push(&mut self, node: &NodeInfo) -> Option<usize> {
+        let ancestor = node
+            .parent
+            .and_then(|parent| self.ancestors_or_self.get(parent).copied().flatten());
+        let index = self.ancestors_or_self.len();
+        self.ancestors_or_self.push(if is_interactive(node) {
+            Some(index)
+        } else {
+            ancestor
+        });
+        ancestor
+    }
+}
+
+/// How many children of each parent carry each trimmed, non-blank name. Shared by
+/// `TreeIndex` and `CollectionStats`, so the nodes flagged and counted as duplicates
+/// agree.
+#[derive(Debug, Clone, Default)]
+pub struct SiblingNames(HashMap<usize, HashMap<String, usize>>);
+
+impl SiblingNames {
+    /// Counts `node`, returning how many of its siblings so far, itself included, carry
+    /// its name; `None` for roots and blank names.
+    pub fn push(&mut self, node: &NodeInfo) -> Option<usize> {
+        let (parent, name) = (node.parent?, node.name.trim());
+        if name.is_empty() {
+            return None;
+        }
+        let count = self.0.entry(parent).or_default();
+        let count = count.entry(name.to_string()).or_default();
+        *count += 1;
+        Some(*count)
+    }
+
+    /// How many children of the parent of `node`, itself included, carry its name.
+    pub fn count(&self, node: &NodeInfo) -> Option<usize> {
+        self.0.get(&node.parent?)?.get(node.name.trim()).copied()
+    }
+}
+
+/// What the rules comparing nodes with each other need to know about the whole tree,
+/// worked out once per tree so that checking a node does not look at every other one.
+///
+/// Relies on parents coming before their children, as they do in traversal order.
+pub struct TreeIndex {
+    /// The nearest interactive ancestor of every node.
+    interactive_ancestors: Vec<Option<usize>>,
+    /// An interactive descendant of every node, if it has any.
+    interactive_descendants: Vec<Option<usize>>,
+    sibling_names: SiblingNames,
+    /// An unrelated interactive node overlapping every interactive node, if any.
+    overlaps: Vec<Option<usize>>,
+}
+
+impl TreeIndex {
+    pub fn new(nodes: &[NodeInfo]) -> Self {
+        let mut ancestry = InteractiveAncestry::default();
+        let mut sibling_names = SiblingNames::default();
+        let interactive_ancestors = nodes
+            .iter()
+            .map(|node| {
+                sibling_names.push(node);
+                ancestry.push(node)
+            })
+            .collect();
+
+        let mut interactive_descendants: Vec<Option<usize>> = vec![None; nodes.len()];
+        for (index, node) in nodes.iter().enumerate().rev() {
+            if let Some(parent) = node.parent {
+                let descendant = if is_interactive(node) {
+                    Some(index)
+                } else {
+                    interactive_descendants[index]
+                };
+                interactive_descendants[parent] = interactive_descendants[parent].or(descendant);
+            }
+        }
+
+        Self {
+            interactive_ancestors,
+            interactive_descendants,
+            sibling_names,
+            overlaps: overlaps(nodes),
+        }
+    }
+}
+
+/// Pairs up overlapping interactive nodes outside each other's ancestry, sweeping their
+/// boxes from left to right so only boxes sharing a column are compared.
+fn overlaps(nodes: &[NodeInfo]) -> Vec<Option<usize>> {
+    let mut boxes: Vec<(usize, Extents)> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| is_interactive(node))
+        .filter_map(|(index, node)| Some((index, node.extents?)))
+        .collect();
+    boxes.sort_by_key(|(_, extents)| extents.x);
+
+    let mut overlaps = vec![None; nodes.len()];
+    let mut active: Vec<(usize, Extents)> = Vec::new();
+    for (index, extents) in boxes {
+        active.retain(|(_, other)| other.x + other.width > extents.x);
+        for &(other, other_extents) in &active {
+            if other_extents.intersects(&extents)
+                && !is_ancestor(nodes, other, index)
+                && !is_ancestor(nodes, index, other)
+            {
+                overlaps[index] = overlaps[index].or(Some(other));
+                overlaps[other] = overlaps[other].or(Some(index));
+            }
+        }
+        active.push((index, extents));
+    }
+    overlaps
+}
+
+/// Flags the node at `index` if its box overlaps that of another interactive node that is
+/// neither its ancestor nor its descendant.
+pub fn overlapping_clickable(nodes: &[NodeInfo], index: usize, tree: &TreeIndex) -> Option<String> {
+    let other = &nodes[tree.overlaps[index]?];
+    Some(format!("overlaps {} '{}'", other.role.name(), other.name))
+}
+
+/// Flags the node at `index` if it is interactive and either inside or around another
+/// interactive node.
+pub fn nested_interactive(nodes: &[NodeInfo], index: usize, tree: &TreeIndex) -> Option<String> {
+    if !is_interactive(&nodes[index]) {
+        return None;
+    }
+
+    if let Some(outer) = tree.interactive_ancestors[index] {
+        let outer = &nodes[outer];
+        return Some(format!("nested in {} '{}'", outer.role.name(), outer.name));
+    }
+    let inner = &nodes[tree.interactive_descendants[index]?];
+    Some(format!("contains {} '{}'", inner.role.name(), inner.name))
+}
+
+/// Flags the node at `index` if other children of its parent have the same non-blank
+/// name.
+pub fn duplicate_sibling_name(
+    nodes: &[NodeInfo],
+    index: usize,
+    tree: &TreeIndex,
+) -> Option<String> {
+    let node = &nodes[index];
+    let name = node.name.trim();
+    match tree.sibling_names.count(node)? - 1 {
+        0 => None,
+        1 => Some(format!("shares name '{name}' with a sibling")),
+        count => Some(format!("shares name '{name}' with {count} siblings")),
+    }
+}
+
+/// The issues found on one node.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub index: usize,
+    pub issues: Vec<String>,
+    /// Whether every rule the node fails is only a warning, see `LintRule::is_warning`.
+    pub warning: bool,
+}
+
+/// Runs the configured rules over every node, returning the flagged ones.
+pub fn lint(nodes: &[NodeInfo], config: &LintConfig) -> Vec<Finding> {
+    let tree = TreeIndex::new(nodes);
+    (0..nodes.len())
+        .filter_map(|index| {
+            let failed: Vec<(LintRule, String)> = config
+                .rules
+                .iter()
+                .filter_map(|rule| Some((*rule, rule.check(nodes, index, &tree, config)?)))
+                .collect();
+            (!failed.is_empty()).then(|| Finding {
+                index,
+                warning: failed.iter().all(|(rule, _)| rule.is_warning()),
+                issues: failed.into_iter().map(|(_, issue)| issue).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::tests::node;
+    use atspi::StateSet;
+
+    /// The node `name` with the `role` and `extents`, a child of `parent`.
+    fn child(
+        parent: usize,
+        role: Role,
+        name: &str,
+        extents: Option<(i32, i32, i32, i32)>,
+    ) -> NodeInfo {
+        NodeInfo {
+            parent: Some(parent),
+            ..node(role, name, extents)
+        }
+    }
+
+    /// A tab stop with the `extents`.
+    fn tab_stop(extents: (i32, i32, i32, i32)) -> NodeInfo {
+        NodeInfo {
+            states: StateSet::new(State::Focusable | State::Sensitive | State::Showing),
+            ..node(Role::Panel, "Pane", Some(extents))
+        }
+    }
+
+    #[test]
+    fn flags_unnamed_interactive_nodes() {
+        assert!(empty_name(&node(Role::Button, " ", None)).is_some());
+        assert!(empty_name(&node(Role::Button, "OK", None)).is_none());
+        assert!(empty_name(&node(Role::Label, "", None)).is_none());
+    }
+
+    #[test]
+    fn flags_images_without_a_text_alternative() {
+        assert!(missing_alt_text(&node(Role::Image, "", None)).is_some());
+        let described = NodeInfo {
+            description: "A cat".into(),
+            ..node(Role::Image, "", None)
+        };
+        assert!(missing_alt_text(&described).is_none());
+    }
+
+    #[test]
+    fn flags_focusable_nodes_without_size() {
+        let mut focusable = tab_stop((10, 10, 0, 20));
+        assert!(zero_size_focusable(&focusable).is_some());
+        focusable.extents = Some(Extents::new((10, 10, 1, 20)));
+        assert!(zero_size_focusable(&focusable).is_none());
+        assert!(zero_size_focusable(&node(Role::Panel, "", Some((10, 10, 0, 0)))).is_none());
+    }
+
+    #[test]
+    fn flags_targets_just_below_the_minimum_size() {
+        let min_size = 24.0;
+        assert!(tiny_target(&node(Role::Button, "OK", Some((0, 0, 24, 24))), min_size).is_none());
+        assert!(tiny_target(&node(Role::Button, "OK", Some((0, 0, 23, 24))), min_size).is_some());
+        assert!(tiny_target(&node(Role::Button, "OK", Some((0, 0, 24, 23))), min_size).is_some());
+        assert!(tiny_target(&node(Role::Label, "OK", Some((0, 0, 8, 8))), min_size).is_none());
+        // Left to `zero_size_focusable`.
+        assert!(tiny_target(&node(Role::Button, "OK", Some((0, 0, 0, 24))), min_size).is_none());
+    }
+
+    #[test]
+    fn flags_offscreen_tab_stops() {
+        let screen = Extents::new((0, 0, 1920, 1080));
+        assert!(offscreen_focusable(&tab_stop((-500, 10, 100, 20)), &screen).is_some());
+        assert!(offscreen_focusable(&tab_stop((100, 10, 100, 20)), &screen).is_none());
+        assert!(
+            offscreen_focusable(&node(Role::Panel, "", Some((-500, 10, 100, 20))), &screen)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn keeps_partly_onscreen_tab_stops() {
+        let screen = Extents::new((0, 0, 1920, 1080));
+        assert!(offscreen_focusable(&tab_stop((-50, 10, 100, 20)), &screen).is_none());
+        assert!(offscreen_focusable(&tab_stop((1900, 1070, 100, 20)), &screen).is_none());
+    }
+
+    #[test]
+    fn flags_tab_stops_just_past_the_screen_edge() {
+        let screen = Extents::new((0, 0, 1920, 1080));
+        // Touching the edge from outside covers no pixel of the screen.
+        assert!(offscreen_focusable(&tab_stop((-100, 10, 100, 20)), &screen).is_some());
+        assert!(offscreen_focusable(&tab_stop((1920, 10, 100, 20)), &screen).is_some());
+        assert!(offscreen_focusable(&tab_stop((10, 1080, 100, 20)), &screen).is_some());
+        // Starting at the edge from inside is on screen.
+        assert!(offscreen_focusable(&tab_stop((0, 0, 100, 20)), &screen).is_none());
+        assert!(offscreen_focusable(&tab_stop((1919, 1079, 100, 20)), &screen).is_none());
+    }
+
+    #[test]
+    fn flags_nested_interactive_nodes_on_both_sides() {
+        let nodes = [
+            node(Role::Button, "Outer", None),
+            child(0, Role::Panel, "", None),
+            child(1, Role::Link, "Inner", None),
+            child(1, Role::Label, "Text", None),
+        ];
+        let tree = TreeIndex::new(&nodes);
+        assert_eq!(
+            nested_interactive(&nodes, 2, &tree).unwrap(),
+            format!("nested in {} 'Outer'", Role::Button.name())
+        );
+        assert_eq!(
+            nested_interactive(&nodes, 0, &tree).unwrap(),
+            format!("contains {} 'Inner'", Role::Link.name())
+        );
+        assert!(nested_interactive(&nodes, 1, &tree).is_none());
+        assert!(nested_interactive(&nodes, 3, &tree).is_none());
+    }
+
+    #[test]
+    fn flags_siblings_sharing_a_name() {
+        let nodes = [
+            node(Role::List, "", None),
+            child(0, Role::ListItem, "Item", None),
+            child(0, Role::ListItem, " Item ", None),
+            child(0, Role::ListItem, "Other", None),
+            child(3, Role::Label, "Item", None),
+        ];
+        let tree = TreeIndex::new(&nodes);
+        for index in [1, 2] {
+            assert_eq!(
+                duplicate_sibling_name(&nodes, index, &tree).unwrap(),
+                "shares name 'Item' with a sibling"
+            );
+        }
+        assert!(duplicate_sibling_name(&nodes, 3, &tree).is_none());
+        assert!(duplicate_sibling_name(&nodes, 4, &tree).is_none());
+    }
+
+    /// The overlaps found among buttons with the `extents`, all children of a window.
+    fn overlaps_of(extents: &[(i32, i32, i32, i32)]) -> Vec<Option<usize>> {
+        let window = node(Role::Frame, "Window", Some((0, 0, 1000, 1000)));
+        let buttons = extents
+            .iter()
+            .map(|&extents| child(0, Role::Button, "OK", Some(extents)));
+        let nodes: Vec<_> = std::iter::once(window).chain(buttons).collect();
+        overlaps(&nodes)
+    }
+
+    #[test]
+    fn does_not_count_touching_edges_as_overlap() {
+        let side_by_side = overlaps_of(&[(0, 0, 10, 10), (10, 0, 10, 10)]);
+        assert_eq!(side_by_side, [None, None, None]);
+        let stacked = overlaps_of(&[(0, 0, 10, 10), (0, 10, 10, 10)]);
+        assert_eq!(stacked, [None, None, None]);
+        let corners = overlaps_of(&[(0, 0, 10, 10), (10, 10, 10, 10)]);
+        assert_eq!(corners, [None, None, None]);
+    }
+
+    #[test]
+    fn counts_overlapping_and_contained_boxes() {
+        let overlapping = overlaps_of(&[(0, 0, 10, 10), (9, 9, 10, 10)]);
+        assert_eq!(overlapping, [None, Some(2), Some(1)]);
+        let contained = overlaps_of(&[(0, 0, 100, 100), (40, 40, 10, 10)]);
+        assert_eq!(contained, [None, Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn skips_boxes_disjoint_on_one_axis() {
+        // Sharing columns but not rows, and rows but not columns.
+        let columns = overlaps_of(&[(0, 0, 10, 10), (5, 50, 10, 10)]);
+        assert_eq!(columns, [None, None, None]);
+        let rows = overlaps_of(&[(0, 0, 10, 10), (50, 5, 10, 10)]);
+        assert_eq!(rows, [None, None, None]);
+        // A wide box stays in the sweep past narrower ones that end before it.
+        let wide = overlaps_of(&[(0, 0, 500, 10), (10, 50, 10, 10), (400, 5, 10, 10)]);
+        assert_eq!(wide, [None, Some(3), None, Some(1)]);
+    }
+
+    #[test]
+    fn names_the_overlapped_node() {
+        let nodes = [
+            node(Role::Frame, "Window", Some((0, 0, 1000, 1000))),
+            child(0, Role::Button, "OK", Some((0, 0, 10, 10))),
+            child(0, Role::Button, "Cancel", Some((5, 5, 10, 10))),
+        ];
+        let tree = TreeIndex::new(&nodes);
+        assert_eq!(
+            overlapping_clickable(&nodes, 1, &tree).unwrap(),
+            format!("overlaps {} 'Cancel'", Role::Button.name())
+        );
+        assert!(overlapping_clickable(&nodes, 0, &tree).is_none());
+    }
+
+    #[test]
+    fn does_not_count_boxes_within_their_ancestors() {
+        let nodes = [
+            node(Role::Button, "Outer", Some((0, 0, 100, 100))),
+            child(0, Role::Link, "Inner", Some((10, 10, 10, 10))),
+        ];
+        assert_eq!(overlaps(&nodes), [None, None]);
+        let tree = TreeIndex::new(&nodes);
+        assert!(overlapping_clickable(&nodes, 1, &tree).is_none());
+    }
+
+    #[test]
+    fn finds_the_nearest_interactive_ancestor() {
+        let nodes = [
+            node(Role::Button, "Outer", None),
+            child(0, Role::Panel, "", None),
+            child(1, Role::Link, "Inner", None),
+            child(2, Role::Label, "", None),
+            node(Role::Panel, "", None),
+        ];
+        let mut ancestry = InteractiveAncestry::default();
+        let ancestors: Vec<_> = nodes.iter().map(|node| ancestry.push(node)).collect();
+        assert_eq!(ancestors, [None, Some(0), Some(0), Some(2), None]);
+    }
+
+    #[test]
+    fn counts_trimmed_sibling_names() {
+        let nodes = [
+            node(Role::List, "Item", None),
+            child(0, Role::ListItem, "Item", None),
+            child(0, Role::ListItem, " Item", None),
+            child(0, Role::ListItem, "  ", None),
+            child(1, Role::Label, "Item", None),
+        ];
+        let mut names = SiblingNames::default();
+        let counts: Vec<_> = nodes.iter().map(|node| names.push(node)).collect();
+        assert_eq!(counts, [None, Some(1), Some(2), None, Some(1)]);
+        assert_eq!(names.count(&nodes[1]), Some(2));
+        assert_eq!(names.count(&nodes[4]), Some(1));
+        assert_eq!(names.count(&nodes[0]), None);
+    }
+
+    #[test]
+    fn lints_only_the_configured_rules() {
+        let nodes = [
+            node(Role::Frame, "Window", Some((0, 0, 800, 600))),
+            child(0, Role::Button, "", Some((10, 10, 100, 30))),
+            child(0, Role::Button, "OK", Some((200, 10, 4, 4))),
+        ];
+        let findings = lint(&nodes, &LintConfig::default());
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].index, 1);
+        assert!(!findings[0].warning);
+        assert_eq!(findings[1].index, 2);
+        assert!(findings[1].warning);
+
+        let config = LintConfig {
+            rules: vec![LintRule::TinyTarget],
+            ..LintConfig::default()
+        };
+        let findings = lint(&nodes, &config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].index, 2);
+    }
+}
@@ -1,196 +1,682 @@
 // Copyright 2025 Colton Loftus
 // SPDX-License-Identifier: AGPL-3.0-only
 
+mod apps;
+mod cli;
+mod collect;
+mod collector;
+mod command;
+mod config;
+mod diff;
+mod error;
+mod error_log;
+mod export;
+mod geometry;
+mod gui;
+mod keys;
+mod lint;
+mod mirror;
+mod monitor;
+mod node;
+mod predicate;
+mod render;
+mod session;
+mod stats;
+mod status;
+
 use atspi::connection::set_session_accessibility;
-use atspi::proxy::accessible::{AccessibleProxy, ObjectRefExt};
+use atspi::proxy::accessible::ObjectRefExt;
+
+use atspi::{
+    AccessibilityConnection, AtspiError, DocumentEvents, Event, ObjectEvents, ObjectRef, Role,
+    State,
+};
 
-use atspi::{DocumentEvents, Event, ObjectRef, State};
+use clap::Parser;
 
-use atspi_proxies::proxy_ext::ProxyExt;
 use eframe::egui;
-use egui::{Color32, Rangef, Rect};
+use log::{debug, error, info, warn};
 
-use futures::executor::block_on;
-use std::error::Error;
+use std::io::IsTerminal;
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
-use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 use tokio_stream::StreamExt;
 use zbus::Connection;
 
-/// Performs a depth-first search to collect children in the accessibility tree.
-async fn dfs_collect_children(
-    root: AccessibleProxy<'_>,
-    conn: &Arc<Connection>,
-) -> Result<Vec<ObjectRef>, Box<dyn Error>> {
-    let mut stack = vec![root];
-    let mut collected = Vec::new();
-
-    while let Some(proxy) = stack.pop() {
-        let children = proxy.get_children().await?;
-
-        for child in children {
-            let child_proxy = child.clone().into_accessible_proxy(&conn).await?;
-
-            stack.push(child_proxy.clone());
-
-            let state = block_on(child_proxy.get_state())?;
+use crate::apps::{
+    desktop_root, find_by_pid, is_idle_focus, list_applications, process_id, registry_version,
+    toolkit_info,
+};
+use crate::cli::{Args, Coordinates, is_wayland, session_type};
+use crate::collect::{CollectOptions, resolve_detached, stream_children, toplevel_window};
+use crate::collector::Collector;
+use crate::command::Command;
+use crate::config::Config;
+use crate::diff::TreeDiff;
+use crate::error::Error;
+use crate::gui::{GuiMessage, GuiSender, ScreenPainterGUI};
+use crate::mirror::Mirror;
+use crate::node::address;
+
+/// Initial size of the window showing trees received with `--connect`.
+const REMOTE_WINDOW_SIZE: [f32; 2] = [1280.0, 800.0];
+
+/// Resolves the newly focused `object` and hands it to the GUI's focus trail.
+async fn send_focused_node(object: ObjectRef, conn: &Arc<Connection>, tx: &GuiSender, args: &Args) {
+    let node = match object.clone().into_accessible_proxy(conn).await {
+        Ok(proxy) => resolve_detached(&proxy, object, &CollectOptions::from(args)).await,
+        Err(err) => Err(err.into()),
+    };
 
-            if state.contains(State::Showing) {
-                collected.push(child);
+    match node {
+        Ok(node) => {
+            if tx.send(GuiMessage::Focused(node)).is_err() {
+                debug!("GUI has shut down, dropping focused node");
             }
         }
+        Err(err) => error!("Error resolving focused node: {err}"),
     }
-    println!("Collected {} children", collected.len());
-    Ok(collected)
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let atspi = Arc::new(atspi::AccessibilityConnection::new().await?);
-    let conn = Arc::new(atspi.connection().clone());
+/// Resolves the top-level window containing the newly focused `object` and hands it to
+/// the GUI.
+async fn send_focused_window(
+    object: ObjectRef,
+    conn: &Arc<Connection>,
+    tx: &GuiSender,
+    args: &Args,
+) {
+    let window = match toplevel_window(object, conn).await {
+        Ok((window, proxy)) => resolve_detached(&proxy, window, &CollectOptions::from(args)).await,
+        Err(err) => Err(err),
+    };
 
-    set_session_accessibility(true).await?;
+    match window {
+        Ok(window) => {
+            if tx.send(GuiMessage::FocusedWindow(window)).is_err() {
+                debug!("GUI has shut down, dropping focused window");
+            }
+        }
+        Err(err) => error!("Error resolving focused window: {err}"),
+    }
+}
 
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_transparent(true)
-            .with_decorations(false)
-            .with_mouse_passthrough(true)
-            .with_fullscreen(true),
-        ..Default::default()
-    };
+/// Pauses or resumes collection depending on whether the newly focused `object` is
+/// worth showing.
+async fn send_idle_state(
+    object: &ObjectRef,
+    conn: &Connection,
+    commands: &UnboundedSender<Command>,
+) {
+    match is_idle_focus(object, conn).await {
+        Ok(idle) => {
+            if commands.send(Command::SetIdle(idle)).is_err() {
+                debug!("Collector has shut down");
+            }
+        }
+        Err(err) => error!("Error checking focused application: {err}"),
+    }
+}
 
-    let (tx_gui, rx_gui) = mpsc::unbounded_channel();
+/// Checks that `root` refers to a live accessible before traversal starts from it.
+async fn validate_root(root: &ObjectRef, conn: &Connection) -> Result<(), Error> {
+    let proxy = root.as_accessible_proxy(conn).await?;
+    proxy.get_role().await.map_err(|err| {
+        Error::Invalid(format!(
+            "Root {} does not resolve to an accessible: {err}",
+            address(root)
+        ))
+    })?;
+    Ok(())
+}
 
-    eframe::run_native(
-        "Atspi Visualizer",
-        options,
-        Box::new({ 
-            let atspi = atspi.clone();
-            let conn = conn.clone();
-            move |cc| {
-                let frame = cc.egui_ctx.clone();
+/// How long the registry has to answer the watchdog's probe before the bus counts as
+/// wedged.
+const WATCHDOG_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
-                tokio::spawn(async move {
-                    frame.request_repaint();
-                });
+/// Registers for the events the visualizer reacts to.
+async fn subscribe(atspi: &AccessibilityConnection, args: &Args) -> Result<(), Error> {
+    if args.focused_window || args.focus_trail || args.idle_when_unfocused {
+        atspi.register_event::<ObjectEvents>().await?;
+    }
+    if !args.focused_window {
+        atspi.register_event::<DocumentEvents>().await?;
+    }
+    Ok(())
+}
 
-                let atspi_clone = atspi.clone();
-                let conn_clone = conn.clone();
-                let tx_gui_clone = tx_gui.clone();
+/// Whether the registry answers a query within `WATCHDOG_PROBE_TIMEOUT`.
+async fn bus_responds(conn: &Connection) -> bool {
+    let probe = async { desktop_root(conn).await?.child_count().await };
+    matches!(
+        tokio::time::timeout(WATCHDOG_PROBE_TIMEOUT, probe).await,
+        Ok(Ok(_))
+    )
+}
 
+/// Listens for AT-SPI events and triggers collections in response.
+///
+/// With `--event-watchdog`, the registry is probed whenever no event arrives for that
+/// long; if it does not answer, the bus is taken to be wedged and the subscription is
+/// set up again, which is logged and shown on the overlay.
+async fn watch_events(
+    atspi: Arc<AccessibilityConnection>,
+    conn: Arc<Connection>,
+    tx: GuiSender,
+    commands: UnboundedSender<Command>,
+    args: Arc<Args>,
+) {
+    subscribe(&atspi, &args).await.unwrap();
+    let mut events = Box::pin(atspi.event_stream());
+    let watchdog =
+        (args.event_watchdog > 0.0).then(|| Duration::from_secs_f64(args.event_watchdog));
+
+    loop {
+        let event = match watchdog {
+            Some(watchdog) => match tokio::time::timeout(watchdog, events.next()).await {
+                Ok(event) => event,
+                Err(_) if bus_responds(&conn).await => {
+                    debug!("No events for {watchdog:?}, but the bus responds");
+                    continue;
+                }
+                Err(_) => {
+                    warn!("No events for {watchdog:?} and the bus does not respond, resubscribing");
+                    if let Err(err) = subscribe(&atspi, &args).await {
+                        error!("Error resubscribing to events: {err}");
+                    }
+                    events = Box::pin(atspi.event_stream());
+                    let notice = "Event stream stalled, resubscribed".to_string();
+                    if tx.send(GuiMessage::Notice(notice)).is_err() {
+                        debug!("GUI has shut down, dropping notice");
+                    }
+                    continue;
+                }
+            },
+            None => events.next().await,
+        };
+        let Some(event) = event else {
+            break;
+        };
+        match event {
+            Ok(Event::Object(ObjectEvents::StateChanged(ev)))
+                if ev.state == State::Focused && ev.enabled =>
+            {
+                let (conn, tx, args) = (conn.clone(), tx.clone(), args.clone());
+                let commands = commands.clone();
                 tokio::spawn(async move {
-                    atspi_clone.register_event::<DocumentEvents>().await.unwrap();
-                    let mut events = atspi_clone.event_stream();
-
-                    while let Some(event) = events.next().await {
-                        match event {
-                            Ok(Event::Document(DocumentEvents::LoadComplete(ev))) => {
-                                let conn_inner = conn_clone.clone();
-                                let tx_inner = tx_gui_clone.clone();
-
-                                tokio::spawn(async move {
-                                   let a11y_proxy = ev.item.into_accessible_proxy(&conn_inner).await;
-                                   match a11y_proxy {
-                                      Ok(proxy) => {
-                                         match dfs_collect_children(proxy, &conn_inner).await {
-                                             Ok(object_refs) => {
-                                                 if let Err(err) = tx_inner.send(object_refs) {
-                                                     eprintln!("Error sending object refs: {err}")
-                                                 }
-                                             }
-                                             Err(err) => eprintln!("Error collecting children: {err}"),
-                                         }
-                                      }
-                                      Err(err) => eprintln!("Error creating proxy: {err}"),
-                                   }
-                                });
-                            }
-                            Ok(_) => println!("Other event"),
-                            Err(err) => println!("Error: {err}"),
-                        }
+                    if args.idle_when_unfocused {
+                        send_idle_state(&ev.item, &conn, &commands).await;
+                    }
+                    if args.focus_trail {
+                        send_focused_node(ev.item.clone(), &conn, &tx, &args).await;
+                    }
+                    if args.focused_window {
+                        send_focused_window(ev.item, &conn, &tx, &args).await;
                     }
                 });
+            }
+            Ok(Event::Document(DocumentEvents::LoadComplete(ev))) => {
+                // The collector decides what, if anything, to re-collect.
+                if commands.send(Command::DocumentLoaded(ev.item)).is_err() {
+                    debug!("Collector has shut down");
+                }
+            }
+            Ok(_) => debug!("Other event"),
+            Err(err) => error!("Error: {err}"),
+        }
+    }
+}
 
-                Ok(Box::new(ScreenPainterGUI::new(conn.clone(), rx_gui)))
+/// Collects trees for `--serve` without an overlay, logging the notices the overlay would
+/// show, until the event stream ends. Returns whether every tree met
+/// `--min-name-coverage`.
+async fn run_headless(
+    atspi: Arc<AccessibilityConnection>,
+    args: Arc<Args>,
+    collector: impl FnOnce(Arc<Connection>, GuiSender) -> Collector,
+) -> bool {
+    let conn = Arc::new(atspi.connection().clone());
+    let (tx_gui, mut rx_gui) = gui::channel(None);
+    let (tx_commands, rx_commands) = mpsc::unbounded_channel();
+    let collector = collector(conn.clone(), tx_gui.clone());
+    let below_coverage = collector.below_coverage();
+    tokio::spawn(collector.run(rx_commands));
+    tokio::spawn(async move {
+        while let Some(message) = rx_gui.recv().await {
+            if let GuiMessage::Notice(notice) = message {
+                info!("{notice}");
             }
-        }),
-    )?;
+        }
+    });
+    let duration = args.duration;
+    let events = watch_events(atspi, conn, tx_gui, tx_commands, args);
+    match duration {
+        Some(seconds) => {
+            if tokio::time::timeout(Duration::from_secs_f64(seconds), events)
+                .await
+                .is_err()
+            {
+                info!("Stopping after {seconds} seconds");
+            }
+        }
+        None => events.await,
+    }
+    !below_coverage.load(Ordering::Relaxed)
+}
+
+/// Connects to the accessibility bus, given by `--bus-address` or announced on the
+/// session bus.
+async fn connect(args: &Args) -> Result<AccessibilityConnection, Error> {
+    match &args.bus_address {
+        Some(address) => AccessibilityConnection::from_address(address.clone())
+            .await
+            .map_err(|source| Error::Connection {
+                bus: address.to_string(),
+                source: source.into(),
+            }),
+        // Enabling accessibility is a session setting, so an explicitly given bus is
+        // expected to be set up already.
+        None => {
+            let session = |source: AtspiError| Error::Connection {
+                bus: "session".into(),
+                source,
+            };
+            let atspi = AccessibilityConnection::new()
+                .await
+                .map_err(|err| session(err.into()))?;
+            set_session_accessibility(true).await.map_err(session)?;
+            Ok(atspi)
+        }
+    }
+}
+
+/// The versions and session details maintainers ask for in bug reports, one per line.
+fn environment() -> Vec<String> {
+    vec![
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        format!("atspi {}", env!("RESOLVED_ATSPI_VERSION")),
+        format!("session {}", session_type()),
+    ]
+}
 
+/// Prints `environment` and the AT-SPI version of the registry.
+async fn print_diagnostics(args: &Args) -> Result<(), Error> {
+    for line in environment() {
+        println!("{line}");
+    }
+    let atspi = connect(args).await?;
+    println!("AT-SPI {}", registry_version(atspi.connection()).await?);
     Ok(())
 }
 
-struct ScreenPainterGUI {
-    conn: Arc<Connection>,
-    points: UnboundedReceiver<Vec<ObjectRef>>,
-    state: Option<Vec<ObjectRef>>,
+/// Connects to the accessibility bus and queries its registry, printing a line per step.
+/// Returns whether every step passed.
+async fn self_test(args: &Args) -> bool {
+    for line in environment() {
+        println!("INFO {line}");
+    }
+    let atspi = match connect(args).await {
+        Ok(atspi) => {
+            println!("PASS connected to the accessibility bus");
+            atspi
+        }
+        Err(err) => {
+            println!("FAIL connecting to the accessibility bus: {err}");
+            return false;
+        }
+    };
+    let conn = atspi.connection();
+
+    let role = match desktop_root(conn).await {
+        Ok(desktop) => desktop.get_role().await.map_err(Error::from),
+        Err(err) => Err(err.into()),
+    };
+    match role {
+        Ok(Role::DesktopFrame) => {
+            println!("PASS registry answers with the desktop");
+            match registry_version(conn).await {
+                Ok(version) => println!("INFO AT-SPI {version}"),
+                Err(err) => println!("INFO AT-SPI version unknown: {err}"),
+            }
+        }
+        Ok(role) => {
+            println!(
+                "FAIL registry answers with a {} instead of the desktop",
+                role.name()
+            );
+            return false;
+        }
+        Err(err) => {
+            println!("FAIL reaching the registry: {err}");
+            return false;
+        }
+    }
+
+    match list_applications(conn).await {
+        Ok(applications) => {
+            println!("PASS {} applications registered", applications.len());
+            if applications.is_empty() {
+                println!("     no application exposes an accessibility tree yet");
+            }
+            true
+        }
+        Err(err) => {
+            println!("FAIL listing applications: {err}");
+            false
+        }
+    }
 }
 
-impl ScreenPainterGUI {
-    fn new(conn: Arc<Connection>, rx_gui: UnboundedReceiver<Vec<ObjectRef>>) -> Self {
-        Self {
-            conn,
-            points: rx_gui,
-            state: None,
+/// Prints the registered applications, aligned for reading or tab-separated with
+/// `--quiet`.
+async fn print_applications(args: &Args) -> Result<(), Error> {
+    let atspi = connect(args).await?;
+    let conn = atspi.connection();
+
+    let mut rows = Vec::new();
+    for application in list_applications(conn).await? {
+        let pid = process_id(&application.object, conn)
+            .await
+            .map_or_else(|_| String::new(), |pid| pid.to_string());
+        let toolkit = toolkit_info(&application.object, conn).await.map_or_else(
+            |_| String::new(),
+            |info| format!("{} {}", info.toolkit, info.version),
+        );
+        rows.push([application.name, pid, toolkit, address(&application.object)]);
+    }
+
+    if args.quiet {
+        for row in rows {
+            println!("{}", row.join("\t"));
+        }
+        return Ok(());
+    }
+    let header = ["NAME", "PID", "TOOLKIT", "ADDRESS"].map(String::from);
+    let mut widths = [0; 4];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
         }
     }
+    for row in std::iter::once(&header).chain(&rows) {
+        let [name, pid, toolkit, address] = row;
+        println!(
+            "{name:<0$}  {pid:>1$}  {toolkit:<2$}  {address}",
+            widths[0], widths[1], widths[2]
+        );
+    }
+    Ok(())
 }
 
-impl eframe::App for ScreenPainterGUI {
-    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
-        egui::Rgba::TRANSPARENT.to_array()
+/// Collects the tree below `--root` once and prints how it differs from the baseline
+/// dump. Returns whether the differences stay within `--diff-threshold` and the tree
+/// meets `--min-name-coverage`.
+async fn diff_baseline(
+    baseline: &Path,
+    conn: &Arc<Connection>,
+    args: &Args,
+    options: CollectOptions,
+) -> Result<bool, Error> {
+    let Some(root) = args.root.clone() else {
+        return Err(Error::Invalid(
+            "--diff-baseline needs --root or --pid to collect from".into(),
+        ));
+    };
+    let baseline = export::read_json(baseline)?;
+
+    let (stream, traversal) = stream_children(root, conn.clone(), options);
+    let mut nodes: Vec<_> = stream.collect().await;
+    let stats = traversal
+        .await
+        .map_err(|err| Error::Invalid(format!("Collection panicked: {err}")))??;
+    info!("{stats}");
+    if stats.focused > 1 {
+        warn!("{} nodes claim focus at once", stats.focused);
+    }
+    for (inner, outer) in &stats.nested_interactive {
+        info!("Interactive {inner} is nested in {outer}");
+    }
+    if stats.duplicate_sibling_names > 0 {
+        warn!(
+            "{} nodes share their name with a sibling",
+            stats.duplicate_sibling_names
+        );
+    }
+    if stats.offscreen_focusable > 0 {
+        warn!(
+            "{} focusable nodes lie off screen",
+            stats.offscreen_focusable
+        );
+    }
+    let covered = match args.min_name_coverage {
+        Some(min_percent) if stats.name_coverage.is_below(min_percent) => {
+            error!(
+                "Error: {} is below the minimum of {min_percent}%",
+                stats.name_coverage
+            );
+            false
+        }
+        _ => true,
+    };
+    if args.anonymize {
+        nodes = export::anonymize(&nodes);
     }
 
-    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
-        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+    let diff = TreeDiff::between(&baseline, &nodes);
+    print!("{diff}");
+    println!(
+        "{} added, {} removed, {} changed (threshold {})",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        args.diff_threshold
+    );
+    Ok(diff.len() <= args.diff_threshold && covered)
+}
 
-        if let Ok(points) = self.points.try_recv() {
-            if !points.is_empty() {
-                self.state = Some(points);
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let (errors, status) = error_log::init();
+    let mut args = Args::parse();
+    if is_wayland() {
+        match args.coordinates {
+            None => {
+                warn!(
+                    "Wayland session: screen coordinates are unreliable, so boxes are placed \
+                     relative to their window and may be offset by its position; pass \
+                     --coordinates screen to override"
+                );
+                args.coordinates = Some(Coordinates::Window);
+            }
+            Some(Coordinates::Screen) => {
+                warn!("Wayland session: screen coordinates may be missing or misplaced")
+            }
+            Some(Coordinates::Window) => {}
+        }
+    }
+    let monitor = match args.monitor {
+        Some(index) => {
+            let monitor = monitor::find_monitor(index)?;
+            info!(
+                "Covering monitor {index} ({}) at {:?}",
+                monitor.name, monitor.extents
+            );
+            args.region = match args.region {
+                Some(region) => Some(region.intersection(&monitor.extents).ok_or_else(|| {
+                    Error::Invalid(format!("--region lies outside monitor {index}"))
+                })?),
+                None => Some(monitor.extents),
+            };
+            Some(monitor.extents)
+        }
+        None => None,
+    };
+    let mut args = Arc::new(args);
+    if args.list_apps {
+        return print_applications(&args).await;
+    }
+    if args.diagnostics {
+        return print_diagnostics(&args).await;
+    }
+    if args.check {
+        std::process::exit(if self_test(&args).await { 0 } else { 1 });
+    }
+    let config_path = args.config.clone().unwrap_or_else(config::default_path);
+    let mut config = Config::load(&config_path)?;
+    let stop_roles = config.traversal.stop_roles()?;
+    let excluded_applications = [&config.exclude_applications[..], &args.exclude_app[..]].concat();
+
+    let overlays = args
+        .overlay
+        .iter()
+        .map(|source| Ok((source.clone(), export::read_json(&source.path)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // Replayed sessions and remote trees are shown without a live connection.
+    let (replay, atspi) = match &args.replay {
+        Some(path) => (session::load(path)?, None),
+        None if args.connect.is_some() => (Vec::new(), None),
+        None => {
+            let atspi = Arc::new(connect(&args).await?);
+            if let Some(pid) = args.pid {
+                let application = find_by_pid(atspi.connection(), pid).await?;
+                info!("Collecting from {} (PID {pid})", application.name);
+                Arc::make_mut(&mut args).root = Some(application.object);
+            }
+            if let Some(root) = &args.root {
+                validate_root(root, atspi.connection()).await?;
             }
+            (Vec::new(), Some(atspi))
         }
+    };
 
-        egui::CentralPanel::default()
-            .frame(egui::Frame::NONE)
-            .show(ctx, |ui| {
-                if let Some(state) = &self.state {
-                    println!("Rendering points: {}", state.len());
-
-                    use futures::stream::StreamExt;
-
-                    let stream = futures::stream::iter(state.iter()).for_each_concurrent(None, |point| {
-                        let conn = self.conn.clone();
-                        let painter = ui.painter();
-
-                        async move {
-                            match point.as_accessible_proxy(&conn).await {
-                                Ok(proxy) => match proxy.proxies().await {
-                                   Ok(proxies) => match proxies.component().await {
-                                      Ok(component) => match component.get_extents(atspi::CoordType::Screen).await {
-                                         Ok((x0, y0, _, _)) => {
-                                             let x_range = Rangef::new(x0 as f32, (x0 as f32) + 10.0);
-                                             let y_range = Rangef::new(y0 as f32, (y0 as f32) + 10.0);
-                                             painter.rect_filled(
-                                                 Rect::from_x_y_ranges(x_range, y_range),
-                                                 0,
-                                                 Color32::RED,
-                                             );
-                                         }
-                                         Err(err) => eprintln!("Error: Failed to get extents from component: {err}"),
-                                      },
-                                      Err(err) => eprintln!("Error: Failed to get component from proxies: {err}"),
-                                   },
-                                   Err(err) => eprintln!("Error: Failed to get proxies from proxy: {err}"),
-                                },
-                                Err(err) => eprintln!("Error: Failed to create AccessibleProxy: {err}"),
-                            }
-                        }
-                    });
-
-                    block_on(stream);
-                }
-            });
+    // Window-relative extents cannot be told to be off screen.
+    if atspi.is_some() && args.coordinates != Some(Coordinates::Window) {
+        match monitor::screen() {
+            Ok(screen) => config.lint.screen = Some(screen),
+            Err(err) => debug!("Not checking for off-screen focusable nodes: {err}"),
+        }
+    }
+
+    let collect_options = CollectOptions {
+        stop_roles: stop_roles.into(),
+        table_cells: config.traversal.table_cells(),
+        max_children: config.traversal.max_children,
+        hyperlinks: config.traversal.hyperlinks,
+        selections: config.traversal.selections,
+        contrast: config.traversal.contrast,
+        lint: config.lint.clone(),
+        ..CollectOptions::from(&*args)
+    };
+    if let (Some(baseline), Some(atspi)) = (&args.diff_baseline, &atspi) {
+        let conn = Arc::new(atspi.connection().clone());
+        let within = diff_baseline(baseline, &conn, &args, collect_options).await?;
+        std::process::exit(if within { 0 } else { 1 });
+    }
+    let mirror = match &args.serve {
+        Some(addr) => Some(Mirror::serve(addr, args.anonymize).await?),
+        None => None,
+    };
+    let collector = {
+        let args = args.clone();
+        move |conn, tx| {
+            Collector::new(conn, tx, args, collect_options, excluded_applications)
+                .with_mirror(mirror)
+        }
+    };
+    // `--serve` conflicts with `--replay`, so there is a live connection.
+    if args.headless
+        && let Some(atspi) = atspi
+    {
+        let covered = run_headless(atspi, args, collector).await;
+        std::process::exit(if covered { 0 } else { 1 });
     }
+
+    // Remote trees are laid out for another screen, so they get a window of their own
+    // rather than an overlay.
+    let mut viewport = match &args.connect {
+        Some(addr) => egui::ViewportBuilder::default()
+            .with_title(format!("Atspi Visualizer: {addr}"))
+            .with_inner_size(REMOTE_WINDOW_SIZE),
+        None => egui::ViewportBuilder::default()
+            .with_transparent(true)
+            .with_decorations(false)
+            .with_mouse_passthrough(args.replay.is_none())
+            .with_always_on_top()
+            .with_fullscreen(true),
+    };
+    // Fullscreen windows cover the monitor they are placed on.
+    if let Some(monitor) = monitor {
+        let rect = monitor.rect();
+        viewport = viewport
+            .with_position(rect.min)
+            .with_inner_size(rect.size());
+    }
+    let options = eframe::NativeOptions {
+        viewport,
+        ..Default::default()
+    };
+
+    let tui_status = if !args.tui_status {
+        None
+    } else if std::io::stderr().is_terminal() {
+        Some(status.clone())
+    } else {
+        warn!("--tui-status needs stderr to be a terminal, not showing a status line");
+        None
+    };
+
+    let (tx_commands, rx_commands) = mpsc::unbounded_channel();
+
+    eframe::run_native(
+        "Atspi Visualizer",
+        options,
+        Box::new(move |cc| {
+            let frame = cc.egui_ctx.clone();
+            let (tx_gui, rx_gui) = gui::channel(Some(cc.egui_ctx.clone()));
+
+            tokio::spawn(async move {
+                frame.request_repaint();
+            });
+
+            if let Some(atspi) = atspi {
+                let conn = Arc::new(atspi.connection().clone());
+                tokio::spawn(collector(conn.clone(), tx_gui.clone()).run(rx_commands));
+
+                tokio::spawn(watch_events(
+                    atspi,
+                    conn,
+                    tx_gui,
+                    tx_commands.clone(),
+                    args.clone(),
+                ));
+            } else if let Some(addr) = args.connect.clone() {
+                tokio::spawn(mirror::receive(addr, tx_gui));
+            }
+
+            Ok(Box::new(
+                ScreenPainterGUI::new(
+                    rx_gui,
+                    tx_commands,
+                    args,
+                    config,
+                    config_path,
+                    errors,
+                    replay,
+                )
+                .with_overlays(overlays)
+                .with_monitor(monitor)
+                .with_status_line(tui_status),
+            ))
+        }),
+    )
+    .map_err(|err| Error::Gui(err.to_string()))?;
+    status.clear();
+
+    Ok(())
 }
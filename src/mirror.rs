@@ -0,0 +1,157 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use log::{debug, info, warn};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+use crate::error::Error;
+use crate::export::anonymize;
+use crate::gui::{GuiMessage, GuiSender};
+use crate::session::Frame;
+
+/// How long the receiver waits before connecting again after losing the collector; the
+/// wait doubles with every failed attempt, up to `MAX_RECONNECT_DELAY`.
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(8);
+
+/// Streams collected trees to remote viewers connected over TCP.
+///
+/// The protocol is the session file format: every tree is sent as a `Frame` serialized
+/// to one line of JSON, so a stream saved with e.g. `nc HOST PORT > session.ndjson` can
+/// be replayed as it is. Viewers receive the newest tree as soon as they connect; a
+/// viewer too slow to keep up skips to the newest tree instead of falling behind.
+#[derive(Debug)]
+pub struct Mirror {
+    /// The newest frame, as the line sent for it.
+    frames: watch::Sender<Option<Arc<str>>>,
+    anonymized: bool,
+}
+
+impl Mirror {
+    /// Listens for viewers on `addr` in the background.
+    pub async fn serve(addr: &str, anonymized: bool) -> Result<Arc<Self>, Error> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|err| Error::Invalid(format!("Failed to listen on {addr}: {err}")))?;
+        if let Ok(local) = listener.local_addr() {
+            info!("Mirroring trees to viewers connecting to {local}");
+        }
+
+        let mirror = Arc::new(Self {
+            frames: watch::Sender::new(None),
+            anonymized,
+        });
+        let accepting = mirror.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        info!("Viewer connected from {peer}");
+                        tokio::spawn(send_frames(stream, peer, accepting.frames.subscribe()));
+                    }
+                    Err(err) => warn!("Error accepting viewer: {err}"),
+                }
+            }
+        });
+        Ok(mirror)
+    }
+
+    /// Sends `frame` to every connected viewer, and to viewers connecting later until a
+    /// newer frame replaces it.
+    pub fn publish(&self, frame: &Frame) -> Result<(), serde_json::Error> {
+        let line = if self.anonymized {
+            let frame = Frame {
+                timestamp: frame.timestamp,
+                toolkit: frame.toolkit.clone(),
+                nodes: anonymize(&frame.nodes),
+            };
+            serde_json::to_string(&frame)?
+        } else {
+            serde_json::to_string(frame)?
+        };
+        self.frames.send_replace(Some(format!("{line}\n").into()));
+        Ok(())
+    }
+}
+
+/// Writes each new frame to the viewer at `peer` until it disconnects.
+async fn send_frames(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    mut frames: watch::Receiver<Option<Arc<str>>>,
+) {
+    // Send the newest frame right away rather than waiting for the next one.
+    frames.mark_changed();
+    while frames.changed().await.is_ok() {
+        let Some(line) = frames.borrow_and_update().clone() else {
+            continue;
+        };
+        if let Err(err) = stream.write_all(line.as_bytes()).await {
+            debug!("Viewer {peer} went away: {err}");
+            break;
+        }
+    }
+    info!("Viewer {peer} disconnected");
+}
+
+/// Shows the trees streamed by the `Mirror` at `addr` until the GUI shuts down,
+/// reconnecting whenever the connection is lost. The GUI is told through
+/// `GuiMessage::Connected` while it is cut off.
+pub async fn receive(addr: String, tx: GuiSender) {
+    let mut delay = RECONNECT_DELAY;
+    let mut collection = 0;
+    loop {
+        match TcpStream::connect(&addr).await {
+            Ok(stream) => {
+                info!("Connected to collector at {addr}");
+                delay = RECONNECT_DELAY;
+                if tx.send(GuiMessage::Connected(true)).is_err() {
+                    return;
+                }
+                let mut lines = BufReader::new(stream).lines();
+                loop {
+                    let frame = match lines.next_line().await {
+                        Ok(Some(line)) => match serde_json::from_str::<Frame>(&line) {
+                            Ok(frame) => frame,
+                            Err(err) => {
+                                warn!("Skipping invalid frame from {addr}: {err}");
+                                continue;
+                            }
+                        },
+                        Ok(None) => {
+                            warn!("Collector at {addr} closed the connection");
+                            break;
+                        }
+                        Err(err) => {
+                            warn!("Lost connection to collector at {addr}: {err}");
+                            break;
+                        }
+                    };
+                    collection += 1;
+                    let messages = [
+                        GuiMessage::Toolkit(frame.toolkit),
+                        GuiMessage::Nodes(collection, frame.nodes),
+                        GuiMessage::TreeFinished(collection),
+                    ];
+                    for message in messages {
+                        if tx.send(message).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            Err(err) => debug!("Error connecting to collector at {addr}: {err}"),
+        }
+
+        if tx.send(GuiMessage::Connected(false)).is_err() {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use egui::Rect;
+use std::process::Command;
+
+use crate::error::Error;
+use crate::node::Extents;
+
+/// A monitor as reported by `xrandr --listactivemonitors`.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    pub name: String,
+    /// Where the monitor sits on the screen AT-SPI coordinates refer to.
+    pub extents: Extents,
+}
+
+/// Lists the active monitors in xrandr's order, which `--monitor` indexes into.
+///
+/// Winit only reports monitors once its event loop runs, after the overlay window is
+/// already created, so they are asked of xrandr instead; under Wayland this lists the
+/// monitors XWayland knows of.
+pub fn list_monitors() -> Result<Vec<Monitor>, Error> {
+    let output = Command::new("xrandr")
+        .arg("--listactivemonitors")
+        .output()
+        .map_err(|err| Error::Invalid(format!("Failed to run xrandr to list monitors: {err}")))?;
+    if !output.status.success() {
+        return Err(Error::Invalid(format!(
+            "xrandr failed to list monitors: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_monitor)
+        .collect())
+}
+
+/// Parses a line such as ` 0: +*DP-1 2560/597x1440/336+0+0  DP-1`, skipping the header.
+fn parse_monitor(line: &str) -> Option<Monitor> {
+    let mut fields = line.split_whitespace();
+    fields.next()?.strip_suffix(':')?.parse::<usize>().ok()?;
+    let name = fields.next()?.trim_start_matches(['+', '*']).to_string();
+    // `width/mm x height/mm + x + y`, where the physical sizes are dropped.
+    let (width, rest) = fields.next()?.split_once('x')?;
+    let mut position = rest.split('+');
+    let height = position.next()?;
+    let dimension = |field: &str| field.split('/').next()?.parse::<i32>().ok();
+    let extents = Extents::new((
+        position.next()?.parse().ok()?,
+        position.next()?.parse().ok()?,
+        dimension(width)?,
+        dimension(height)?,
+    ));
+    Some(Monitor { name, extents })
+}
+
+/// The bounds of all active monitors together, i.e. the screen AT-SPI coordinates can
+/// fall on.
+pub fn screen() -> Result<Extents, Error> {
+    list_monitors()?
+        .into_iter()
+        .map(|monitor| monitor.extents.rect())
+        .reduce(Rect::union)
+        .map(Extents::from_rect)
+        .ok_or_else(|| Error::Invalid("xrandr lists no active monitors".into()))
+}
+
+/// Returns the monitor at `index`, failing with the list of monitors if there is none.
+pub fn find_monitor(index: usize) -> Result<Monitor, Error> {
+    let monitors = list_monitors()?;
+    match monitors.get(index) {
+        Some(monitor) => Ok(monitor.clone()),
+        None => {
+            let available: Vec<String> = monitors
+                .iter()
+                .enumerate()
+                .map(|(index, monitor)| format!("{index}: {}", monitor.name))
+                .collect();
+            Err(Error::Invalid(format!(
+                "There is no monitor {index}; the active monitors are {}",
+                available.join(", ")
+            )))
+        }
+    }
+}
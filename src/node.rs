@@ -0,0 +1,526 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::{Action, Interface, ObjectRef, Role, State, StateSet};
+use egui::{Pos2, Rect};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Screen extents of an accessible as reported by the Component interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extents {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Extents {
+    pub fn new((x, y, width, height): (i32, i32, i32, i32)) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect::from_min_size(
+            Pos2::new(self.x as f32, self.y as f32),
+            egui::vec2(self.width.max(0) as f32, self.height.max(0) as f32),
+        )
+    }
+
+    /// Whether the position is real rather than a placeholder toolkits report when they
+    /// do not know it, i.e. `i32::MIN` or `(-1, -1)`.
+    pub fn has_position(&self) -> bool {
+        self.x != i32::MIN && self.y != i32::MIN && (self.x, self.y) != (-1, -1)
+    }
+
+    /// Whether the extents cover any area.
+    pub fn has_size(&self) -> bool {
+        self.width > 0 && self.height > 0
+    }
+
+    /// Rounds `rect` outwards to whole pixels.
+    pub fn from_rect(rect: Rect) -> Self {
+        let (min, max) = (rect.min.floor(), rect.max.ceil());
+        Self {
+            x: min.x as i32,
+            y: min.y as i32,
+            width: (max.x - min.x) as i32,
+            height: (max.y - min.y) as i32,
+        }
+    }
+
+    /// Whether every edge of the two areas is at most `tolerance` pixels apart.
+    pub fn matches(&self, other: &Extents, tolerance: i32) -> bool {
+        (self.x - other.x).abs() <= tolerance
+            && (self.y - other.y).abs() <= tolerance
+            && (self.x + self.width - other.x - other.width).abs() <= tolerance
+            && (self.y + self.height - other.y - other.height).abs() <= tolerance
+    }
+
+    /// The area covered by both, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &Extents) -> Option<Extents> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Some(Self::new((x, y, right - x, bottom - y)))
+    }
+
+    /// Whether the two areas overlap; touching edges do not count.
+    pub fn intersects(&self, other: &Extents) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+impl FromStr for Extents {
+    type Err = String;
+
+    /// Parses `x,y,width,height`.
+    fn from_str(region: &str) -> Result<Self, Self::Err> {
+        let parts = region
+            .split(',')
+            .map(|part| part.trim().parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("'{region}' is not a list of integers: {err}"))?;
+        let [x, y, width, height] = parts[..] else {
+            return Err(format!("'{region}' should be x,y,width,height"));
+        };
+        if width <= 0 || height <= 0 {
+            return Err(format!("'{region}' has no area"));
+        }
+        Ok(Self::new((x, y, width, height)))
+    }
+}
+
+/// The current value and range of a node implementing the Value interface.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeValue {
+    pub current: f64,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+impl NodeValue {
+    /// Position of the current value within its range, clamped to `0.0..=1.0`.
+    pub fn fraction(&self) -> f32 {
+        let span = self.maximum - self.minimum;
+        if span <= 0.0 {
+            return 0.0;
+        }
+        ((self.current - self.minimum) / span).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// The layout of a node implementing the Table interface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub rows: i32,
+    pub columns: i32,
+    /// Row and column headers, then cells in row-major order; cells spanning several rows
+    /// or columns are listed once. Cut short after the configured maximum.
+    pub cells: Vec<TableCell>,
+}
+
+/// A cell or header of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TableCell {
+    pub extents: Extents,
+    pub header: bool,
+}
+
+/// The selected children of a container implementing the Selection interface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionInfo {
+    /// How many children the container reports as selected.
+    pub count: i32,
+    /// The extents of the selected children that have any, cut short after
+    /// `collect::MAX_SELECTED_CHILDREN`.
+    pub selected: Vec<Extents>,
+}
+
+/// The colors a node's text is drawn in, from its Text attributes, and how well they
+/// contrast.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextContrast {
+    pub foreground: [u8; 3],
+    pub background: [u8; 3],
+    /// The WCAG contrast ratio, from 1 for equal colors to 21 for black on white.
+    pub ratio: f64,
+}
+
+impl TextContrast {
+    /// The contrast of the `fg-color` and `bg-color` text attributes, if both are set.
+    pub fn from_attributes(attributes: &HashMap<String, String>) -> Option<Self> {
+        let foreground = parse_text_color(attributes.get("fg-color")?)?;
+        let background = parse_text_color(attributes.get("bg-color")?)?;
+        let (lighter, darker) = {
+            let (a, b) = (
+                relative_luminance(foreground),
+                relative_luminance(background),
+            );
+            (a.max(b), a.min(b))
+        };
+        Some(Self {
+            foreground,
+            background,
+            ratio: (lighter + 0.05) / (darker + 0.05),
+        })
+    }
+}
+
+/// Parses a color text attribute, which toolkits write either as `r,g,b`, with 16-bit
+/// components in GTK's case, or as CSS `rgb(r, g, b)`.
+fn parse_text_color(value: &str) -> Option<[u8; 3]> {
+    let value = value.trim();
+    let components = value
+        .strip_prefix("rgb(")
+        .and_then(|value| value.strip_suffix(')'))
+        .unwrap_or(value);
+    let components: Vec<u32> = components
+        .split(',')
+        .map(|component| component.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [r, g, b] = components[..] else {
+        return None;
+    };
+    let max = if r.max(g).max(b) > 255 { 65535 } else { 255 };
+    let scale = |component: u32| (component.min(max) * 255 / max) as u8;
+    Some([scale(r), scale(g), scale(b)])
+}
+
+/// The relative luminance of an sRGB color as defined by WCAG 2.
+fn relative_luminance(color: [u8; 3]) -> f64 {
+    let linear = |component: u8| {
+        let c = f64::from(component) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let [r, g, b] = color.map(linear);
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// A link embedded in the text of a node implementing the Hypertext interface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hyperlink {
+    /// Character offsets of the link's text, end exclusive.
+    pub start: i32,
+    pub end: i32,
+    pub uri: String,
+    /// The bounds of the link's text, if the node implements Text and reports them.
+    pub extents: Option<Extents>,
+}
+
+/// The extents of a node relative to both the screen and its window, queried together
+/// for the inspector.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BothExtents {
+    pub screen: Extents,
+    pub window: Extents,
+}
+
+/// A collected node in the accessibility tree.
+///
+/// Nodes are stored in a flat `Vec` in traversal order; the hierarchy is preserved
+/// through `parent`, which indexes into that same `Vec`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub object: ObjectRef,
+    pub parent: Option<usize>,
+    /// Distance from the collection root, whose direct children are at depth 1.
+    pub depth: usize,
+    pub states: StateSet,
+    pub role: Role,
+    pub name: String,
+    pub description: String,
+    pub extents: Option<Extents>,
+    /// Only resolved when value display is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<NodeValue>,
+    /// The contents of an editable text node, only resolved when value display is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Only resolved for tables when enabled in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<TableInfo>,
+    /// Only resolved for containers implementing Selection when enabled in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<SelectionInfo>,
+    /// Only resolved for hypertext nodes when enabled in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyperlinks: Option<Vec<Hyperlink>>,
+    /// Only resolved for text nodes when enabled in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contrast: Option<TextContrast>,
+    /// How many children the node has, if more than the configured `max_children` were
+    /// left out of the traversal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated_children: Option<usize>,
+    /// Only resolved when the node is selected in the inspector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub both_extents: Option<BothExtents>,
+    /// Only resolved on demand from the inspector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interfaces: Option<Vec<Interface>>,
+    /// Only resolved on demand from the inspector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<Action>>,
+}
+
+impl NodeInfo {
+    /// Whether the node is an image or icon, which needs a text alternative.
+    pub fn is_image(&self) -> bool {
+        matches!(self.role, Role::Image | Role::Icon | Role::DesktopIcon)
+    }
+
+    /// Whether keyboard focus can currently reach the node, i.e. it is a tab stop: it is
+    /// focusable, sensitive, and showing.
+    pub fn is_tab_stop(&self) -> bool {
+        self.states.contains(State::Focusable)
+            && self.states.contains(State::Sensitive)
+            && self.states.contains(State::Showing)
+    }
+
+    /// A short label such as `push button: OK`, or just the role if the node is unnamed.
+    pub fn label(&self) -> String {
+        label(self.role, &self.name)
+    }
+
+    /// Whether the node has a non-blank accessible name or description.
+    pub fn has_text_alternative(&self) -> bool {
+        !self.name.trim().is_empty() || !self.description.trim().is_empty()
+    }
+}
+
+/// A state worth highlighting when coloring nodes by state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignificantState {
+    Focused,
+    Selected,
+    Checked,
+    /// Not sensitive, i.e. greyed out.
+    Disabled,
+}
+
+/// Picks the most significant of `states`, in the order of `SignificantState`; `None` if
+/// the node is in none of them.
+pub fn significant_state(states: StateSet) -> Option<SignificantState> {
+    if states.contains(State::Focused) {
+        Some(SignificantState::Focused)
+    } else if states.contains(State::Selected) {
+        Some(SignificantState::Selected)
+    } else if states.contains(State::Checked) {
+        Some(SignificantState::Checked)
+    } else if !states.contains(State::Sensitive) {
+        Some(SignificantState::Disabled)
+    } else {
+        None
+    }
+}
+
+/// Formats a node's role and name as in `NodeInfo::label`.
+pub fn label(role: Role, name: &str) -> String {
+    if name.is_empty() {
+        role.name().to_string()
+    } else {
+        format!("{}: {name}", role.name())
+    }
+}
+
+/// Formats `object` as its bus name immediately followed by its object path, the same
+/// form accepted by `--root`.
+pub fn address(object: &ObjectRef) -> String {
+    format!("{}{}", object.name, object.path)
+}
+
+/// Returns the indices of nodes in `current` whose extents, name, or states differ from
+/// the node with the same object in `previous`. Nodes new to `current` are not included.
+pub fn changed_nodes(previous: &[NodeInfo], current: &[NodeInfo]) -> Vec<usize> {
+    let previous: HashMap<&ObjectRef, &NodeInfo> =
+        previous.iter().map(|node| (&node.object, node)).collect();
+
+    current
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| {
+            previous.get(&node.object).is_some_and(|old| {
+                old.extents != node.extents || old.name != node.name || old.states != node.states
+            })
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Marks which nodes belong to the subtree rooted at the node at `root`, itself included.
+///
+/// Relies on collection order, in which every node comes after its parent.
+pub fn subtree_members(nodes: &[NodeInfo], root: usize) -> Vec<bool> {
+    let mut members = vec![false; nodes.len()];
+    for index in root..nodes.len() {
+        members[index] = index == root || nodes[index].parent.is_some_and(|p| members[p]);
+    }
+    members
+}
+
+/// Returns the nodes marked in `keep`, re-pointing each one's `parent` at its nearest
+/// kept ancestor so the result is a tree in collection order again.
+pub fn subset(nodes: &[NodeInfo], keep: &[bool]) -> Vec<NodeInfo> {
+    // For each node, the index in the result of itself or its nearest kept ancestor.
+    let mut kept_as: Vec<Option<usize>> = Vec::with_capacity(nodes.len());
+    let mut result = Vec::new();
+    for (index, node) in nodes.iter().enumerate() {
+        let parent = node.parent.and_then(|parent| kept_as[parent]);
+        if keep[index] {
+            kept_as.push(Some(result.len()));
+            result.push(NodeInfo {
+                parent,
+                ..node.clone()
+            });
+        } else {
+            kept_as.push(parent);
+        }
+    }
+    result
+}
+
+/// Whether the node at `ancestor` is an ancestor of the node at `index`.
+pub fn is_ancestor(nodes: &[NodeInfo], ancestor: usize, mut index: usize) -> bool {
+    while let Some(parent) = nodes[index].parent {
+        if parent == ancestor {
+            return true;
+        }
+        index = parent;
+    }
+    false
+}
+
+/// Whether the node at `index` covers the same area as its parent, give or take
+/// `tolerance` pixels, and so adds no spatial information of its own.
+pub fn is_wrapper(nodes: &[NodeInfo], index: usize, tolerance: i32) -> bool {
+    let node = &nodes[index];
+    node.parent
+        .and_then(|parent| nodes[parent].extents)
+        .zip(node.extents)
+        .is_some_and(|(parent, extents)| extents.matches(&parent, tolerance))
+}
+
+/// Returns the index of the top-level ancestor of the node at `index`.
+pub fn top_level_ancestor(nodes: &[NodeInfo], mut index: usize) -> usize {
+    while let Some(parent) = nodes[index].parent {
+        index = parent;
+    }
+    index
+}
+
+/// The name of the application the node at `index` belongs to: its nearest ancestor-or-self
+/// with the application role, as in whole-desktop trees. `None` for trees collected below
+/// their application.
+pub fn application_of(nodes: &[NodeInfo], mut index: usize) -> Option<&str> {
+    loop {
+        if nodes[index].role == Role::Application {
+            return Some(&nodes[index].name);
+        }
+        index = nodes[index].parent?;
+    }
+}
+
+/// Picks a hue in `0.0..1.0` for the group at `index`, such as all descendants of one
+/// top-level container.
+pub fn group_hue(index: usize) -> f32 {
+    // Golden-ratio hue stepping keeps neighbouring groups visually apart.
+    (index as f32 * 0.618_034).fract()
+}
+
+/// Picks a hue in `0.0..1.0` for an application by hashing its `name`, so it keeps its
+/// color across collections and runs.
+pub fn application_hue(name: &str) -> f32 {
+    (fnv1a(name) % 3600) as f32 / 3600.0
+}
+
+/// Hashes `text` with 64-bit FNV-1a.
+///
+/// Unlike `DefaultHasher`, the output is stable across runs and Rust releases.
+pub(crate) fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A collected node with the `role`, `name`, and `extents`, and nothing else resolved.
+    pub(crate) fn node(role: Role, name: &str, extents: Option<(i32, i32, i32, i32)>) -> NodeInfo {
+        NodeInfo {
+            object: ObjectRef::default(),
+            parent: None,
+            depth: 1,
+            states: StateSet::empty(),
+            role,
+            name: name.into(),
+            description: String::new(),
+            extents: extents.map(Extents::new),
+            value: None,
+            text: None,
+            table: None,
+            selection: None,
+            hyperlinks: None,
+            contrast: None,
+            truncated_children: None,
+            both_extents: None,
+            interfaces: None,
+            actions: None,
+        }
+    }
+
+    #[test]
+    fn hashes_with_fnv1a() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(application_hue("gedit"), application_hue("gedit"));
+    }
+
+    #[test]
+    fn prefers_the_most_significant_state() {
+        let significant = |states| significant_state(StateSet::new(states));
+        let all = State::Focused | State::Selected | State::Checked;
+        assert_eq!(significant(all), Some(SignificantState::Focused));
+        assert_eq!(
+            significant(State::Selected | State::Checked),
+            Some(SignificantState::Selected)
+        );
+        // Without Sensitive, each of these is also disabled.
+        assert_eq!(
+            significant(State::Selected.into()),
+            Some(SignificantState::Selected)
+        );
+        assert_eq!(
+            significant(State::Checked.into()),
+            Some(SignificantState::Checked)
+        );
+        assert_eq!(
+            significant(State::Showing.into()),
+            Some(SignificantState::Disabled)
+        );
+    }
+
+    #[test]
+    fn finds_no_significant_state_in_enabled_plain_nodes() {
+        let states = StateSet::new(State::Sensitive | State::Showing | State::Focusable);
+        assert_eq!(significant_state(states), None);
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::{State, StateSet};
+use std::str::FromStr;
+
+use crate::node::NodeInfo;
+
+/// A conjunction of required and forbidden states a node must satisfy to be collected.
+///
+/// Parsed from expressions such as `focusable & !editable` or `focusable AND NOT
+/// editable`; terms may also be separated by commas or whitespace. State names are the
+/// AT-SPI names, e.g. `showing`, `focusable`, `selected`, `multi-line`, plus `disabled`
+/// for nodes that are not `sensitive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatePredicate {
+    required: StateSet,
+    forbidden: StateSet,
+}
+
+impl Default for StatePredicate {
+    /// Only nodes that are showing.
+    fn default() -> Self {
+        Self {
+            required: StateSet::new(State::Showing),
+            forbidden: StateSet::empty(),
+        }
+    }
+}
+
+impl StatePredicate {
+    pub fn matches(&self, states: StateSet) -> bool {
+        (states & self.required) == self.required && (states & self.forbidden).is_empty()
+    }
+}
+
+/// A case-insensitive search for nodes whose name or value contains some text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSearch {
+    pub query: String,
+    /// Match against accessible names.
+    pub names: bool,
+    /// Match against the current value of Value nodes and the contents of editable text
+    /// nodes, which are only known with `--show-values`.
+    pub values: bool,
+}
+
+impl Default for TextSearch {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            names: true,
+            values: false,
+        }
+    }
+}
+
+impl TextSearch {
+    /// Whether there is anything to search for.
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty() && (self.names || self.values)
+    }
+
+    pub fn matches(&self, node: &NodeInfo) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        let query = self.query.trim().to_lowercase();
+        let contains = |text: &str| text.to_lowercase().contains(&query);
+        (self.names && contains(&node.name))
+            || (self.values
+                && (node.text.as_deref().is_some_and(contains)
+                    || node
+                        .value
+                        .is_some_and(|value| contains(&value.current.to_string()))))
+    }
+}
+
+/// Parses a state name, returning the state along with whether the name means its absence.
+fn parse_state(name: &str) -> Result<(State, bool), String> {
+    match State::from(name) {
+        // AT-SPI has no disabled state, only the lack of a sensitive one.
+        State::Invalid if name == "disabled" => Ok((State::Sensitive, true)),
+        State::Invalid if name != "invalid" => Err(format!("unknown state '{name}'")),
+        state => Ok((state, false)),
+    }
+}
+
+impl FromStr for StatePredicate {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let mut required = StateSet::empty();
+        let mut forbidden = StateSet::empty();
+        let mut negate = false;
+
+        let terms = expression
+            .split(|c: char| c == '&' || c == ',' || c.is_whitespace())
+            .filter(|term| !term.is_empty());
+
+        for term in terms {
+            match term.to_ascii_lowercase().as_str() {
+                "and" => continue,
+                "not" => negate = !negate,
+                term => {
+                    let (negated, name) = match term.strip_prefix('!') {
+                        Some(name) => (!negate, name),
+                        None => (negate, term),
+                    };
+                    let (state, absent) = parse_state(name)?;
+                    if negated != absent {
+                        forbidden.insert(state);
+                    } else {
+                        required.insert(state);
+                    }
+                    negate = false;
+                }
+            }
+        }
+
+        if negate {
+            return Err(format!("'{expression}' ends with a dangling NOT"));
+        }
+        if !(required & forbidden).is_empty() {
+            return Err(format!("'{expression}' both requires and forbids a state"));
+        }
+        Ok(Self {
+            required,
+            forbidden,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predicate(required: &[State], forbidden: &[State]) -> StatePredicate {
+        StatePredicate {
+            required: required.iter().copied().collect(),
+            forbidden: forbidden.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn parses_words() {
+        assert_eq!(
+            "focusable AND NOT disabled".parse(),
+            Ok(predicate(&[State::Focusable, State::Sensitive], &[]))
+        );
+    }
+
+    #[test]
+    fn parses_symbols() {
+        assert_eq!(
+            "!disabled,showing".parse(),
+            Ok(predicate(&[State::Sensitive, State::Showing], &[]))
+        );
+        assert_eq!(
+            "showing & !editable".parse(),
+            Ok(predicate(&[State::Showing], &[State::Editable]))
+        );
+    }
+
+    #[test]
+    fn rejects_dangling_not() {
+        let err = "showing NOT".parse::<StatePredicate>().unwrap_err();
+        assert!(err.contains("dangling NOT"), "{err}");
+    }
+
+    #[test]
+    fn rejects_requiring_and_forbidding() {
+        let err = "showing & !showing".parse::<StatePredicate>().unwrap_err();
+        assert!(err.contains("both requires and forbids"), "{err}");
+    }
+
+    #[test]
+    fn rejects_unknown_states() {
+        let err = "showing & shiny".parse::<StatePredicate>().unwrap_err();
+        assert_eq!(err, "unknown state 'shiny'");
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Shape, Stroke, StrokeKind, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// How a line is broken up, so lines can be told apart without relying on their color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinePattern {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LinePattern {
+    /// The lengths of the dashes and of the gaps between them for a stroke of `width`, or
+    /// `None` for a solid line.
+    pub fn dash_gap(self, width: f32) -> Option<(f32, f32)> {
+        match self {
+            Self::Solid => None,
+            Self::Dashed => Some((4.0 * width.max(1.5), 3.0 * width.max(1.5))),
+            Self::Dotted => Some((width, 2.0 * width.max(1.0))),
+        }
+    }
+}
+
+/// The drawing primitives the overlay is painted with.
+///
+/// Everything in `gui::paint` only draws through this trait, so the overlay can be
+/// rendered by backends other than egui, e.g. a terminal or an SVG writer. Geometry and
+/// colors use egui's plain value types, which do not depend on a running GUI.
+pub trait Renderer {
+    /// Fills `rect`, rounding its corners by `rounding` pixels.
+    fn rect_filled(&mut self, rect: Rect, rounding: f32, color: Color32);
+
+    /// Outlines `rect` with the stroke drawn inside or outside its edges.
+    fn rect_stroke(&mut self, rect: Rect, rounding: f32, stroke: Stroke, kind: StrokeKind);
+
+    fn line(&mut self, from: Pos2, to: Pos2, stroke: Stroke);
+
+    fn circle_filled(&mut self, center: Pos2, radius: f32, color: Color32);
+
+    /// Fills and outlines the convex polygon through `points`.
+    fn convex_polygon(&mut self, points: Vec<Pos2>, fill: Color32, stroke: Stroke);
+
+    /// The size of `text` in `font` when wrapped at `wrap_width`.
+    fn text_size(&self, text: &str, font: &FontId, wrap_width: f32) -> Vec2;
+
+    /// Draws `text` in `font` wrapped at `wrap_width`, placing its `anchor` point at
+    /// `pos`.
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font: FontId,
+        color: Color32,
+        wrap_width: f32,
+    );
+
+    /// Scales the opacity of everything drawn afterwards, from 0 for invisible to 1 for
+    /// fully opaque. Backends that cannot blend may ignore it.
+    fn set_opacity(&mut self, _opacity: f32) {}
+
+    /// Draws a line from `from` to `to` in dashes of `dash` pixels separated by `gap`.
+    fn dashed_line(&mut self, from: Pos2, to: Pos2, stroke: Stroke, dash: f32, gap: f32) {
+        let length = from.distance(to);
+        if length <= 0.0 || dash <= 0.0 {
+            return;
+        }
+        let direction = (to - from) / length;
+        let mut start = 0.0;
+        while start < length {
+            let end = (start + dash).min(length);
+            self.line(from + direction * start, from + direction * end, stroke);
+            start = end + gap;
+        }
+    }
+
+    /// Outlines `rect` inside its edges in `pattern`. Patterned outlines have square
+    /// corners, as only solid ones are rounded by `rounding`.
+    fn patterned_rect_stroke(
+        &mut self,
+        rect: Rect,
+        rounding: f32,
+        stroke: Stroke,
+        pattern: LinePattern,
+    ) {
+        let Some((dash, gap)) = pattern.dash_gap(stroke.width) else {
+            self.rect_stroke(rect, rounding, stroke, StrokeKind::Inside);
+            return;
+        };
+        let rect = rect.shrink(stroke.width / 2.0);
+        let corners = [
+            rect.left_top(),
+            rect.right_top(),
+            rect.right_bottom(),
+            rect.left_bottom(),
+        ];
+        for (i, &corner) in corners.iter().enumerate() {
+            self.dashed_line(corner, corners[(i + 1) % 4], stroke, dash, gap);
+        }
+    }
+
+    /// Fills `rect` with diagonal lines `spacing` pixels apart.
+    fn hatch(&mut self, rect: Rect, stroke: Stroke, spacing: f32) {
+        let (width, height) = (rect.width(), rect.height());
+        if spacing <= 0.0 || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        // Each line runs down and to the left, from the top or right edge to the left or
+        // bottom edge.
+        let mut offset = spacing;
+        while offset < width + height {
+            let start = if offset <= width {
+                Pos2::new(rect.min.x + offset, rect.min.y)
+            } else {
+                Pos2::new(rect.max.x, rect.min.y + offset - width)
+            };
+            let end = if offset <= height {
+                Pos2::new(rect.min.x, rect.min.y + offset)
+            } else {
+                Pos2::new(rect.min.x + offset - height, rect.max.y)
+            };
+            self.line(start, end, stroke);
+            offset += spacing;
+        }
+    }
+
+    /// Draws a line from `origin` along `vector` with a head at its tip.
+    fn arrow(&mut self, origin: Pos2, vector: Vec2, stroke: Stroke) {
+        let tip = origin + vector;
+        let head = vector.normalized() * (vector.length() / 4.0);
+        let rotation = egui::emath::Rot2::from_angle(std::f32::consts::TAU / 12.0);
+        self.line(origin, tip, stroke);
+        self.line(tip, tip - rotation * head, stroke);
+        self.line(tip, tip - rotation.inverse() * head, stroke);
+    }
+}
+
+impl Renderer for Painter {
+    fn rect_filled(&mut self, rect: Rect, rounding: f32, color: Color32) {
+        Painter::rect_filled(self, rect, rounding, color);
+    }
+
+    fn rect_stroke(&mut self, rect: Rect, rounding: f32, stroke: Stroke, kind: StrokeKind) {
+        Painter::rect_stroke(self, rect, rounding, stroke, kind);
+    }
+
+    fn line(&mut self, from: Pos2, to: Pos2, stroke: Stroke) {
+        self.line_segment([from, to], stroke);
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        Painter::set_opacity(self, opacity);
+    }
+
+    fn circle_filled(&mut self, center: Pos2, radius: f32, color: Color32) {
+        Painter::circle_filled(self, center, radius, color);
+    }
+
+    fn convex_polygon(&mut self, points: Vec<Pos2>, fill: Color32, stroke: Stroke) {
+        self.add(Shape::convex_polygon(points, fill, stroke));
+    }
+
+    fn text_size(&self, text: &str, font: &FontId, wrap_width: f32) -> Vec2 {
+        self.layout(text.to_string(), font.clone(), Color32::WHITE, wrap_width)
+            .size()
+    }
+
+    fn text(
+        &mut self,
+        pos: Pos2,
+        anchor: Align2,
+        text: &str,
+        font: FontId,
+        color: Color32,
+        wrap_width: f32,
+    ) {
+        let galley = self.layout(text.to_string(), font, color, wrap_width);
+        let rect = anchor.anchor_size(pos, galley.size());
+        self.galley(rect.min, galley, color);
+    }
+}
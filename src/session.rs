@@ -0,0 +1,99 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::apps::ToolkitInfo;
+use crate::error::Error;
+use crate::export::anonymize;
+use crate::node::NodeInfo;
+
+/// One tree of a recorded session, stored as a line of NDJSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    /// When the tree finished collecting, in seconds since the Unix epoch.
+    pub timestamp: f64,
+    /// The toolkit of the application the tree was collected from, if it reported one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolkit: Option<ToolkitInfo>,
+    pub nodes: Vec<NodeInfo>,
+}
+
+impl Frame {
+    /// A frame of `nodes` stamped with the current time.
+    pub fn now(nodes: Vec<NodeInfo>, toolkit: Option<ToolkitInfo>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        Self {
+            timestamp,
+            toolkit,
+            nodes,
+        }
+    }
+}
+
+/// Appends frames to a session file.
+#[derive(Debug)]
+pub struct Recorder {
+    writer: Mutex<BufWriter<File>>,
+    anonymized: bool,
+}
+
+impl Recorder {
+    /// Opens `path` for appending, creating it if needed, so stopping and restarting a
+    /// recording continues the same session.
+    pub fn open(path: &Path, anonymized: bool) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+            anonymized,
+        })
+    }
+
+    /// Writes `frame` as one line and flushes it, so a session cut short by a crash
+    /// still holds every frame recorded until then.
+    pub fn record(&self, mut frame: Frame) -> io::Result<()> {
+        if self.anonymized {
+            frame.nodes = anonymize(&frame.nodes);
+        }
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, &frame)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+/// Reads the frames of the session recorded at `path`, oldest first.
+pub fn load(path: &Path) -> Result<Vec<Frame>, Error> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        Error::Invalid(format!("Failed to read session {}: {err}", path.display()))
+    })?;
+    let frames: Vec<Frame> = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                Error::Invalid(format!(
+                    "Invalid frame on line {} of {}: {err}",
+                    number + 1,
+                    path.display()
+                ))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    if frames.is_empty() {
+        return Err(Error::Invalid(format!(
+            "Session {} has no frames",
+            path.display()
+        )));
+    }
+    Ok(frames)
+}
@@ -0,0 +1,276 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use atspi::State;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::LintConfig;
+use crate::lint::{
+    InteractiveAncestry, SiblingNames, is_interactive, low_contrast, offscreen_focusable,
+    tiny_target,
+};
+use crate::node::NodeInfo;
+
+/// How many interactive nodes, as judged by `lint::is_interactive`, have a non-blank
+/// accessible name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct NameCoverage {
+    pub interactive: usize,
+    pub named: usize,
+}
+
+impl NameCoverage {
+    /// The coverage of every node in `nodes`.
+    pub fn of(nodes: &[NodeInfo]) -> Self {
+        let mut coverage = Self::default();
+        for node in nodes {
+            coverage.record(node);
+        }
+        coverage
+    }
+
+    /// Counts `node` if it is interactive.
+    pub fn record(&mut self, node: &NodeInfo) {
+        if is_interactive(node) {
+            self.interactive += 1;
+            if !node.name.trim().is_empty() {
+                self.named += 1;
+            }
+        }
+    }
+
+    /// The percentage of interactive nodes that are named, or `None` if there are none.
+    pub fn percent(&self) -> Option<f64> {
+        (self.interactive > 0).then(|| self.named as f64 * 100.0 / self.interactive as f64)
+    }
+
+    /// Whether the coverage falls short of `min_percent`; never for trees without
+    /// interactive nodes.
+    pub fn is_below(&self, min_percent: f64) -> bool {
+        self.percent().is_some_and(|percent| percent < min_percent)
+    }
+}
+
+impl fmt::Display for NameCoverage {
+    /// E.g. `name coverage 75% (3 of 4 interactive nodes)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percent() {
+            Some(percent) => write!(
+                f,
+                "name coverage {percent:.0}% ({} of {} interactive nodes)",
+                self.named, self.interactive
+            ),
+            None => write!(f, "name coverage n/a (no interactive nodes)"),
+        }
+    }
+}
+
+/// Where a traversal spent its time waiting on D-Bus, for `--export-folded`.
+#[derive(Debug, Clone, Default)]
+pub struct TraversalProfile {
+    /// Listing the children of the collection root.
+    pub root: Duration,
+    /// Per collected node, in collection order: resolving it, listing its children, and
+    /// querying the state of descendants that were not collected themselves.
+    pub nodes: Vec<Duration>,
+}
+
+impl TraversalProfile {
+    /// Adds `cost` to the node at `index`, or to the root if `None`.
+    pub fn charge(&mut self, index: Option<usize>, cost: Duration) {
+        match index {
+            Some(index) => {
+                if self.nodes.len() <= index {
+                    self.nodes.resize(index + 1, Duration::ZERO);
+                }
+                self.nodes[index] += cost;
+            }
+            None => self.root += cost,
+        }
+    }
+}
+
+/// A summary of one collection, logged when it completes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CollectionStats {
+    /// Number of collected nodes.
+    pub total: usize,
+    /// Number of collected nodes per role name.
+    pub roles: BTreeMap<String, usize>,
+    /// Deepest level a collected node was found at.
+    pub max_depth: usize,
+    /// Wall-clock duration of the traversal.
+    pub elapsed_secs: f64,
+    /// Defunct nodes skipped along with their subtrees.
+    pub skipped: usize,
+    /// Nodes that could not be queried and were left out along with their subtrees.
+    pub errored: usize,
+    /// Interactive nodes smaller than the minimum target size, see `lint::tiny_target`.
+    pub tiny_targets: usize,
+    /// Text nodes whose colors contrast less than `LintConfig::min_contrast`; only
+    /// counted when contrast is queried.
+    pub low_contrast: usize,
+    /// Nodes sharing their non-blank name with a sibling, see
+    /// `lint::duplicate_sibling_name`.
+    pub duplicate_sibling_names: usize,
+    /// The sibling names of the nodes so far, to find duplicates with.
+    #[serde(skip)]
+    sibling_names: SiblingNames,
+    /// Interactive nodes nested in another one, as their labels and that of their nearest
+    /// interactive ancestor; see `lint::nested_interactive`.
+    pub nested_interactive: Vec<(String, String)>,
+    /// The interactive ancestors of the nodes so far, to find nested ones with.
+    #[serde(skip)]
+    interactive_ancestry: InteractiveAncestry,
+    /// Labels of the interactive nodes so far, by index.
+    #[serde(skip)]
+    interactive_labels: HashMap<usize, String>,
+    /// Tab stops lying off the screen, see `lint::offscreen_focusable`; only counted when
+    /// the screen's bounds are known.
+    pub offscreen_focusable: usize,
+    /// Nodes in the focused state; more than one means the application is confused
+    /// about where focus is.
+    pub focused: usize,
+    pub name_coverage: NameCoverage,
+    /// Only recorded when profiling is enabled.
+    #[serde(skip)]
+    pub profile: Option<TraversalProfile>,
+    /// Whether the traversal was stopped early, e.g. because a newer collection started.
+    pub cancelled: bool,
+}
+
+impl CollectionStats {
+    /// Counts a newly collected `node`, checking it against the thresholds in `lint`.
+    pub fn record(&mut self, node: &NodeInfo, lint: &LintConfig) {
+        let index = self.total;
+        self.total += 1;
+        let outer = self.interactive_ancestry.push(node);
+        if is_interactive(node) {
+            let label = node.label();
+            if let Some(outer) = outer {
+                let outer = self.interactive_labels[&outer].clone();
+                self.nested_interactive.push((label.clone(), outer));
+            }
+            self.interactive_labels.insert(index, label);
+        }
+        if tiny_target(node, lint.min_target_size).is_some() {
+            self.tiny_targets += 1;
+        }
+        if low_contrast(node, lint.min_contrast).is_some() {
+            self.low_contrast += 1;
+        }
+        if lint
+            .screen
+            .is_some_and(|screen| offscreen_focusable(node, &screen).is_some())
+        {
+            self.offscreen_focusable += 1;
+        }
+        // The first duplicate flags the sibling it duplicates along with itself.
+        self.duplicate_sibling_names += match self.sibling_names.push(node) {
+            None | Some(1) => 0,
+            Some(2) => 2,
+            Some(_) => 1,
+        };
+        if node.states.contains(State::Focused) {
+            self.focused += 1;
+        }
+        self.max_depth = self.max_depth.max(node.depth);
+        *self.roles.entry(node.role.name().to_string()).or_default() += 1;
+        self.name_coverage.record(node);
+    }
+}
+
+impl fmt::Display for CollectionStats {
+    /// One line, e.g. `Collected 12 nodes in 0.05s (max depth 4, 1 skipped, 0 errored,
+    /// 2 tiny targets, 0 low contrast, 0 duplicate sibling names, 0 nested interactive,
+    /// 0 off-screen focusable, 1 focused, name coverage 80% (4 of 5 interactive nodes)):
+    /// 7 label, 5 push button`, with the most common roles first. Cancelled traversals
+    /// start with `Cancelled after collecting` instead.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cancelled {
+            write!(f, "Cancelled after collecting")?;
+        } else {
+            write!(f, "Collected")?;
+        }
+        write!(
+            f,
+            " {} nodes in {:.2}s (max depth {}, {} skipped, {} errored, {} tiny targets, {} low contrast, {} duplicate sibling names, {} nested interactive, {} off-screen focusable, {} focused, {})",
+            self.total,
+            self.elapsed_secs,
+            self.max_depth,
+            self.skipped,
+            self.errored,
+            self.tiny_targets,
+            self.low_contrast,
+            self.duplicate_sibling_names,
+            self.nested_interactive.len(),
+            self.offscreen_focusable,
+            self.focused,
+            self.name_coverage
+        )?;
+
+        let mut roles: Vec<_> = self.roles.iter().collect();
+        roles.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (i, (role, count)) in roles.into_iter().enumerate() {
+            let separator = if i == 0 { ": " } else { ", " };
+            write!(f, "{separator}{count} {role}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{TreeIndex, duplicate_sibling_name, nested_interactive};
+    use crate::node::tests::node;
+    use atspi::Role;
+
+    #[test]
+    fn counts_what_lint_flags() {
+        let child = |parent, role, name| NodeInfo {
+            parent: Some(parent),
+            ..node(role, name, None)
+        };
+        let nodes = [
+            node(Role::Frame, "Window", None),
+            child(0, Role::Button, "Outer"),
+            child(1, Role::Link, "Inner"),
+            child(1, Role::Link, "Inner"),
+            child(0, Role::Label, "Text"),
+            child(0, Role::Label, "Text"),
+            child(0, Role::Label, " Text "),
+            child(2, Role::Label, "Inner"),
+        ];
+        let mut stats = CollectionStats::default();
+        for node in &nodes {
+            stats.record(node, &LintConfig::default());
+        }
+
+        let tree = TreeIndex::new(&nodes);
+        let flagged = |rule: fn(&[NodeInfo], usize, &TreeIndex) -> Option<String>, prefix| {
+            (0..nodes.len())
+                .filter(|&index| {
+                    rule(&nodes, index, &tree).is_some_and(|issue| issue.starts_with(prefix))
+                })
+                .count()
+        };
+        assert_eq!(stats.duplicate_sibling_names, 5);
+        assert_eq!(
+            stats.duplicate_sibling_names,
+            flagged(duplicate_sibling_name, "")
+        );
+        assert_eq!(stats.nested_interactive.len(), 2);
+        assert_eq!(
+            stats.nested_interactive.len(),
+            flagged(nested_interactive, "nested in")
+        );
+        assert_eq!(
+            stats.nested_interactive[0],
+            (nodes[2].label(), nodes[1].label())
+        );
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2025 Colton Loftus
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// Erases the terminal line the cursor is on and returns to its start.
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// A line at the bottom of the terminal, rewritten in place by `--tui-status`.
+///
+/// Log records go through `around`, so they are printed above the line instead of being
+/// mixed into it.
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    line: Mutex<Option<String>>,
+}
+
+impl StatusLine {
+    /// Replaces the shown line with `line`.
+    pub fn update(&self, line: String) {
+        let mut current = self.line.lock().unwrap();
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "{CLEAR_LINE}{line}");
+        let _ = stderr.flush();
+        *current = Some(line);
+    }
+
+    /// Erases the line for good, e.g. when the overlay exits.
+    pub fn clear(&self) {
+        if self.line.lock().unwrap().take().is_some() {
+            eprint!("{CLEAR_LINE}");
+        }
+    }
+
+    /// Runs `print` with the line erased, then draws it again below whatever was printed.
+    pub fn around(&self, print: impl FnOnce()) {
+        let current = self.line.lock().unwrap();
+        let Some(line) = &*current else {
+            return print();
+        };
+        eprint!("{CLEAR_LINE}");
+        print();
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "{line}");
+        let _ = stderr.flush();
+    }
+}